@@ -0,0 +1,59 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Resolves the path to the `azalia-config` crate from the `Cargo.toml` of the crate that
+//! is using the derive macros, so that `#[merge(crate = ...)]` is only required when the
+//! crate can't be found.
+
+use proc_macro_crate::{FoundCrate, crate_name};
+use proc_macro2::Span;
+use syn::{Ident, PathSegment, Token};
+
+/// Returns the path to the `azalia-config` crate, which is resolved in this order:
+///
+/// 1. `azalia-config` as a direct dependency, which can be renamed;
+/// 2. `azalia` as a direct dependency with the `config` feature, which can be renamed;
+/// 3. `::azalia::config` if neither was found.
+pub fn resolve() -> syn::Path {
+    if let Ok(found) = crate_name("azalia-config") {
+        return match found {
+            // `azalia-config` declares `extern crate self as azalia_config` so that its own
+            // doctests and modules can use the derive macros.
+            FoundCrate::Itself => path(&["azalia_config"]),
+            FoundCrate::Name(name) => path(&[&name]),
+        };
+    }
+
+    match crate_name("azalia") {
+        Ok(FoundCrate::Name(name)) => path(&[&name, "config"]),
+        _ => path(&["azalia", "config"]),
+    }
+}
+
+fn path(segments: &[&str]) -> syn::Path {
+    syn::Path {
+        leading_colon: Some(Token![::](Span::call_site())),
+        segments: segments
+            .iter()
+            .map(|segment| PathSegment::from(Ident::new(segment, Span::call_site())))
+            .collect(),
+    }
+}
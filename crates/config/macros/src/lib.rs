@@ -23,8 +23,12 @@
 #![doc(html_favicon_url = "https://cdn.floofy.dev/images/trans.png")]
 #![cfg_attr(any(noeldoc, docsrs), feature(doc_cfg))]
 
+mod krate;
 mod merge;
 
+#[cfg(feature = "schema")]
+mod schema;
+
 #[cfg(feature = "unstable")]
 mod tryfromenv;
 
@@ -35,6 +39,9 @@ use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 ///
 /// [**`Merge`**]: trait.Merge.html
 ///
+/// This also implements `TryMerge` with the derived `Merge` implementation (when it is available),
+/// so the type can be used as a field of a <code>#[derive(TryMerge)]</code> type.
+///
 /// ## Example
 /// > **NOTE**: This will require the `macros` feature for `azalia_config` or `config+macros` for
 /// > the `azalia` crate.
@@ -49,6 +56,7 @@ use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 /// # }
 /// #
 /// use azalia::config::merge::Merge;
+/// use std::collections::BTreeMap;
 ///
 /// #[derive(Debug, Merge, Default, PartialEq)]
 /// pub struct Config {
@@ -59,13 +67,17 @@ use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 ///
 ///     #[merge(strategy = "i32::merge")]
 ///     pub c: i32,
+///
+///     // colliding keys are merged with `Merge::merge` instead of being replaced
+///     #[merge(deep)]
+///     pub d: BTreeMap<String, Vec<String>>,
 /// }
 ///
 /// let mut config = Config::default();
-/// assert_eq!(config, Config { a: String::default(), b: false, c: 0i32 });
+/// assert_eq!(config, Config { a: String::default(), b: false, c: 0i32, d: BTreeMap::new() });
 ///
-/// config.merge(Config { a: "hello world".into(), b: true, c: 9 });
-/// assert_eq!(config, Config { a: "hello world".into(), b: false, c: 42 });
+/// config.merge(Config { a: "hello world".into(), b: true, c: 9, d: BTreeMap::new() });
+/// assert_eq!(config, Config { a: "hello world".into(), b: false, c: 42, d: BTreeMap::new() });
 ///
 /// // strategies can be regular functions that will expand
 /// // to a fn that implements `(&mut self, Self)`
@@ -80,17 +92,137 @@ use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput};
 pub fn Merge(input: TokenStream) -> TokenStream {
     let derive = parse_macro_input!(input as DeriveInput);
     match &derive.data {
-        Data::Struct(s) => merge::expand_struct(&derive, &s.fields).into(),
-        Data::Enum(e) => syn::Error::new(e.enum_token.span(), "merge trait for enumerations are not supported")
+        Data::Struct(s) => merge::expand_struct(&derive, &s.fields, merge::Derive::Merge).into(),
+        Data::Enum(e) => merge::expand_enumeration(&derive, e, merge::Derive::Merge).into(),
+
+        Data::Union(u) => syn::Error::new(u.union_token.span(), "merge trait for unions will never be supported")
             .into_compile_error()
             .into(),
+    }
+}
+
+/// Procedural macro to implement the [**`TryMerge`**] trait from `azalia::config`'s `merge` module.
+///
+/// [**`TryMerge`**]: trait.TryMerge.html
+///
+/// This supports the same attributes as <code>#[derive(Merge)]</code> with the addition of
+/// `#[merge(conflict = "error")]` and `#[merge(try_strategy = <path>)]` for fields.
+///
+/// ## Example
+/// ```ignore
+/// use azalia::config::merge::TryMerge;
+///
+/// #[derive(Debug, TryMerge, Default, PartialEq)]
+/// pub struct Config {
+///     pub database: Database,
+/// }
+///
+/// #[derive(Debug, TryMerge, Default, PartialEq)]
+/// pub struct Database {
+///     #[merge(conflict = "error")]
+///     pub url: String,
+/// }
+///
+/// let mut config = Config::default();
+/// config.try_merge(Config { database: Database { url: "postgres://a".into() } }).unwrap();
+///
+/// let conflict = config
+///     .try_merge(Config { database: Database { url: "postgres://b".into() } })
+///     .unwrap_err();
+///
+/// assert_eq!(conflict.path(), "database.url");
+/// ```
+#[allow(non_snake_case)]
+#[proc_macro_derive(TryMerge, attributes(merge))]
+pub fn TryMerge(input: TokenStream) -> TokenStream {
+    let derive = parse_macro_input!(input as DeriveInput);
+    match &derive.data {
+        Data::Struct(s) => merge::expand_struct(&derive, &s.fields, merge::Derive::TryMerge).into(),
+        Data::Enum(e) => merge::expand_enumeration(&derive, e, merge::Derive::TryMerge).into(),
+        Data::Union(u) => syn::Error::new(u.union_token.span(), "merge trait for unions will never be supported")
+            .into_compile_error()
+            .into(),
+    }
+}
 
+/// Procedural macro to implement the [**`Merge3`**] trait from `azalia::config`'s `merge` module.
+///
+/// [**`Merge3`**]: trait.Merge3.html
+///
+/// This shares the same attributes as <code>#[derive(Merge)]</code>, where only `#[merge(skip)]`
+/// and `#[merge(three_way = <path>)]` are used for a field.
+///
+/// ## Example
+/// ```ignore
+/// use azalia::config::merge::Merge3;
+///
+/// #[derive(Debug, Merge3, Clone, PartialEq)]
+/// pub struct Config {
+///     pub name: String,
+///     pub port: u16,
+/// }
+///
+/// let base = Config { name: "azalia".into(), port: 8080 };
+///
+/// // `name` was changed at runtime and `port` was changed in the file
+/// let mut ours = Config { name: "noelware".into(), port: 8080 };
+/// ours.merge3(base, Config { name: "azalia".into(), port: 3000 });
+///
+/// assert_eq!(ours, Config { name: "noelware".into(), port: 3000 });
+/// ```
+#[allow(non_snake_case)]
+#[proc_macro_derive(Merge3, attributes(merge))]
+pub fn Merge3(input: TokenStream) -> TokenStream {
+    let derive = parse_macro_input!(input as DeriveInput);
+    match &derive.data {
+        Data::Struct(s) => merge::expand_struct(&derive, &s.fields, merge::Derive::Merge3).into(),
+        Data::Enum(e) => merge::expand_enumeration(&derive, e, merge::Derive::Merge3).into(),
         Data::Union(u) => syn::Error::new(u.union_token.span(), "merge trait for unions will never be supported")
             .into_compile_error()
             .into(),
     }
 }
 
+/// Procedural macro to implement the [**`JsonSchema`**] trait from `azalia::config`'s `schema` module.
+///
+/// [**`JsonSchema`**]: trait.JsonSchema.html
+///
+/// ## Example
+/// > **NOTE**: This will require the `macros` and `schema` features for `azalia_config` or
+/// > `config+macros` and `config+schema` for the `azalia` crate.
+///
+/// ```ignore
+/// use azalia::config::{merge::Merge, schema::{self, JsonSchema}};
+///
+/// #[derive(Merge, JsonSchema, Default, serde::Serialize)]
+/// #[schema(default, rename_all = "snake_case")]
+/// pub struct Config {
+///     /// Port to listen on.
+///     pub port: u16,
+///
+///     /// Logging level, defaults to `info` if not present.
+///     #[schema(default = "info")]
+///     pub level: Option<String>,
+///
+///     #[schema(skip)]
+///     pub internal: bool,
+/// }
+///
+/// let document = schema::document::<Config>();
+/// assert_eq!(document["required"], serde_json::json!(["port"]));
+/// assert_eq!(document["properties"]["port"]["description"], "Port to listen on.");
+/// ```
+#[allow(non_snake_case)]
+#[cfg(feature = "schema")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "schema")))]
+#[proc_macro_derive(JsonSchema, attributes(schema))]
+pub fn JsonSchema(input: TokenStream) -> TokenStream {
+    let derive = parse_macro_input!(input as DeriveInput);
+    schema::expand(&derive)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
 /// Procedural macro to implement [`TryFromEnv`] for `struct`s.
 ///
 /// [`TryFromEnv`]: trait.TryFromEnv.html
@@ -19,12 +19,17 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned, ToTokens};
+mod partial;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
 use syn::{
+    Attribute, DataEnum, DeriveInput, Expr, ExprPath, Fields, GenericArgument, Generics, Ident, LitStr, Member, Meta,
+    PathArguments, PathSegment, Token, Type, WherePredicate,
+    meta::ParseNestedMeta,
     parse::{Parse, ParseStream},
+    punctuated::Punctuated,
     spanned::Spanned,
-    Attribute, DeriveInput, ExprPath, Fields, Ident, LitStr, Member, Meta, PathSegment, Token,
 };
 
 pub struct Path(pub(crate) ExprPath);
@@ -36,7 +41,10 @@ impl Parse for Path {
         } else if let Ok(s) = input.parse::<LitStr>() {
             s.parse::<ExprPath>().map(Self)
         } else {
-            Err(syn::Error::new(s, "expected either a qualified path (i.e, `std::mem::replace`) or a literal string that can be a qualified path"))
+            Err(syn::Error::new(
+                s,
+                "expected either a qualified path (i.e, `std::mem::replace`) or a literal string that can be a qualified path",
+            ))
         }
     }
 }
@@ -47,8 +55,92 @@ impl ToTokens for Path {
     }
 }
 
+/// A strategy is either a [`Path`] to a function or a call that returns a strategy, like
+/// `strategy::vec::merge_by_key(|plugin: &Plugin| plugin.name.clone())`.
+pub struct Strategy(Expr);
+impl Parse for Strategy {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let s = input.span();
+        if input.peek(LitStr) {
+            return input.parse::<Path>().map(|Path(path)| Strategy(Expr::Path(path)));
+        }
+
+        match input.parse::<Expr>() {
+            Ok(expr @ (Expr::Path(_) | Expr::Call(_))) => Ok(Strategy(expr)),
+            _ => Err(syn::Error::new(
+                s,
+                "expected either a qualified path (i.e, `std::mem::replace`), a call that returns a strategy or a literal string that can be a qualified path",
+            )),
+        }
+    }
+}
+
+impl ToTokens for Strategy {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.0.to_tokens(tokens);
+    }
+}
+
+/// **#\[merge(default)\]** | **#\[merge(default = "overwrite")\]** | **#\[merge(default = "fill")\]**
+#[derive(Clone, Copy)]
+pub enum DefaultMode {
+    /// Uses `strategy::default::overwrite`, the overlay wins if it is not `Default::default()`.
+    Overwrite,
+
+    /// Uses `strategy::default::fill`, the base wins if it is not `Default::default()`.
+    Fill,
+}
+
+impl DefaultMode {
+    fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        if !meta.input.peek(Token![=]) {
+            return Ok(DefaultMode::Overwrite);
+        }
+
+        let lit = meta.value()?.parse::<LitStr>()?;
+        match &*lit.value() {
+            "overwrite" => Ok(DefaultMode::Overwrite),
+            "fill" => Ok(DefaultMode::Fill),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "expected either `\"overwrite\"` or `\"fill\"`",
+            )),
+        }
+    }
+
+    fn to_strategy(self, krate: &Path) -> TokenStream {
+        match self {
+            DefaultMode::Overwrite => quote!(#krate::merge::strategy::default::overwrite),
+            DefaultMode::Fill => quote!(#krate::merge::strategy::default::fill),
+        }
+    }
+}
+
 pub struct Container {
     pub krate: Path,
+
+    /// **#\[merge(default)\]**: default-aware merging for all fields.
+    pub default: Option<DefaultMode>,
+
+    /// **#\[merge(strategy = ...)\]**: merges the whole enumeration with a strategy
+    /// instead of merging matching variants, or the default strategy for all fields
+    /// of a struct.
+    pub strategy: Option<Strategy>,
+
+    /// **#\[merge(strategy_for(Type) = ...)\]**: the default strategy for all fields
+    /// of a type.
+    pub strategy_for: Vec<(Type, Strategy)>,
+
+    /// **#\[merge(bound = "...")\]**: replaces the bounds that are inferred from the
+    /// type parameters.
+    pub bound: Option<Vec<WherePredicate>>,
+
+    /// **#\[merge(partial)\]**: generates a sparse overlay of a struct.
+    pub partial: Option<partial::Options>,
+
+    /// **#\[merge(recurse_options)\]**: uses `strategy::option::recurse` for all `Option<T>` fields, or
+    /// `strategy::option::try_recurse` for `TryMerge`.
+    pub recurse_options: Option<Span>,
 }
 
 impl Default for Container {
@@ -57,18 +149,15 @@ impl Default for Container {
             krate: Path(ExprPath {
                 attrs: Vec::new(),
                 qself: None,
-
-                // ::azalia::config
-                path: syn::Path {
-                    leading_colon: Some(Token![::](Span::call_site())),
-                    segments: [
-                        PathSegment::from(Ident::new("azalia", Span::call_site())),
-                        PathSegment::from(Ident::new("config", Span::call_site())),
-                    ]
-                    .into_iter()
-                    .collect(),
-                },
+                path: crate::krate::resolve(),
             }),
+
+            default: None,
+            strategy: None,
+            strategy_for: Vec::new(),
+            bound: None,
+            partial: None,
+            recurse_options: None,
         }
     }
 }
@@ -88,6 +177,168 @@ impl Parse for Container {
     }
 }
 
+impl Container {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Container::default();
+        for attr in attrs {
+            if !attr.path().is_ident("merge") {
+                continue;
+            }
+
+            if let Meta::List(list) = &attr.meta
+                && list.tokens.is_empty()
+            {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    container.krate = meta.value()?.parse()?;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("strategy") {
+                    let input = meta.value()?;
+                    if container.strategy.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "container already has `#[merge(strategy)]`",
+                        ));
+                    }
+
+                    container.strategy = Some(input.parse()?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("strategy_for") {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+
+                    let ty = content.parse::<Type>()?;
+                    let strategy = meta.value()?.parse()?;
+                    container.strategy_for.push((ty, strategy));
+
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("bound") {
+                    if container.bound.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "container already has `#[merge(bound)]`",
+                        ));
+                    }
+
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    let predicates = lit.parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+                    container.bound = Some(predicates.into_iter().collect());
+
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("default") {
+                    if container.default.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "container already has `#[merge(default)]`",
+                        ));
+                    }
+
+                    container.default = Some(DefaultMode::parse(&meta)?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("partial") {
+                    if container.partial.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "container already has `#[merge(partial)]`",
+                        ));
+                    }
+
+                    container.partial = Some(partial::Options::parse(&meta)?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("recurse_options") {
+                    if container.recurse_options.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "container already has `#[merge(recurse_options)]`",
+                        ));
+                    }
+
+                    container.recurse_options = Some(meta.path.span());
+                    return Ok(());
+                }
+
+                Err(meta.error(
+                    "unknown field, expected either `crate`, `strategy`, `strategy_for`, `bound`, `default`, `partial`, `recurse_options`",
+                ))
+            })?;
+        }
+
+        Ok(container)
+    }
+
+    /// Returns the strategy from `#[merge(strategy_for(...))]` that matches `ty`. A type
+    /// without any generic arguments, like `Vec`, matches all of its instantiations.
+    fn strategy_for(&self, ty: &Type) -> Option<&Strategy> {
+        fn last_segment(ty: &Type) -> Option<&PathSegment> {
+            match ty {
+                Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+                _ => None,
+            }
+        }
+
+        self.strategy_for.iter().find_map(|(pattern, strategy)| {
+            let matches = match (last_segment(pattern), last_segment(ty)) {
+                (Some(expected), Some(actual)) if expected.arguments.is_none() => expected.ident == actual.ident,
+                _ => pattern.to_token_stream().to_string() == ty.to_token_stream().to_string(),
+            };
+
+            matches.then_some(strategy)
+        })
+    }
+
+    /// Returns the generics of the implementation: either the bounds from `#[merge(bound)]`,
+    /// or `T: Trait` for each type parameter that is used by a field merged with `trait_`.
+    fn generics<'a>(
+        &self,
+        generics: &Generics,
+        trait_: TokenStream,
+        bounded: impl IntoIterator<Item = &'a Type>,
+    ) -> Generics {
+        let mut generics = generics.clone();
+        let predicates = match &self.bound {
+            Some(bound) => bound.clone(),
+            None => {
+                let bounded = bounded.into_iter().collect::<Vec<_>>();
+                generics
+                    .type_params()
+                    .filter(|param| bounded.iter().any(|ty| mentions(ty.to_token_stream(), &param.ident)))
+                    .map(|param| {
+                        let ident = &param.ident;
+                        syn::parse_quote!(#ident: #trait_)
+                    })
+                    .collect()
+            }
+        };
+
+        generics.make_where_clause().predicates.extend(predicates);
+        generics
+    }
+}
+
+/// Returns `true` if `ident` is used anywhere in `tokens`.
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|tree| match tree {
+        TokenTree::Ident(other) => other == *ident,
+        TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
 mod kw {
     syn::custom_keyword!(strategy);
     syn::custom_keyword!(skip);
@@ -96,13 +347,562 @@ mod kw {
 #[derive(Default)]
 pub struct Field {
     pub skipped: bool,
-    pub strategy: Option<Path>,
+    pub strategy: Option<Strategy>,
+
+    /// **#\[merge(deep)\]**: uses `strategy::maps::deep` for map-like fields.
+    pub deep: Option<Span>,
+
+    /// **#\[merge(default)\]**: default-aware merging for this field.
+    pub default: Option<(Span, DefaultMode)>,
+
+    /// **#\[merge(try_strategy = ...)\]**: a fallible strategy, only for `TryMerge`.
+    pub try_strategy: Option<Strategy>,
+
+    /// **#\[merge(conflict = "error")\]**: uses `strategy::conflict::error`, only for `TryMerge`.
+    pub conflict: Option<Span>,
+
+    /// **#\[merge(partial)\]**: uses the field type's partial in the container's partial.
+    pub partial: Option<Span>,
+
+    /// **#\[merge(three_way = ...)\]**: a three-way strategy, only for `Merge3`.
+    pub three_way: Option<Strategy>,
+}
+
+impl Field {
+    fn from_attrs(attrs: &[Attribute], derive: Derive) -> syn::Result<Self> {
+        let mut field = Field::default();
+        for attr in attrs {
+            if !attr.path().is_ident("merge") {
+                continue;
+            }
+
+            if let Meta::List(list) = &attr.meta
+                && list.tokens.is_empty()
+            {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    if field.skipped {
+                        return Err(syn::Error::new(meta.path.span(), "field already has `#[merge(skip)]`"));
+                    }
+
+                    field.skipped = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("strategy") {
+                    let input = meta.value()?;
+                    if field.strategy.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "field already has `#[merge(strategy)]`",
+                        ));
+                    }
+
+                    field.strategy = Some(input.parse()?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("deep") {
+                    if field.deep.is_some() {
+                        return Err(syn::Error::new(meta.path.span(), "field already has `#[merge(deep)]`"));
+                    }
+
+                    field.deep = Some(meta.path.span());
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("default") {
+                    if field.default.is_some() {
+                        return Err(syn::Error::new(meta.path.span(), "field already has `#[merge(default)]`"));
+                    }
+
+                    field.default = Some((meta.path.span(), DefaultMode::parse(&meta)?));
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("partial") {
+                    if field.partial.is_some() {
+                        return Err(syn::Error::new(meta.path.span(), "field already has `#[merge(partial)]`"));
+                    }
+
+                    field.partial = Some(meta.path.span());
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("three_way") {
+                    let input = meta.value()?;
+                    if field.three_way.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "field already has `#[merge(three_way)]`",
+                        ));
+                    }
+
+                    field.three_way = Some(input.parse()?);
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("try_strategy") || meta.path.is_ident("conflict") {
+                    let name = if meta.path.is_ident("conflict") { "conflict" } else { "try_strategy" };
+                    if derive != Derive::TryMerge {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            format!("`#[merge({name})]` is only supported by `#[derive(TryMerge)]`"),
+                        ));
+                    }
+
+                    if field.try_strategy.is_some() || field.conflict.is_some() {
+                        return Err(syn::Error::new(
+                            meta.path.span(),
+                            "field already has either `#[merge(try_strategy)]` or `#[merge(conflict)]`",
+                        ));
+                    }
+
+                    if name == "try_strategy" {
+                        field.try_strategy = Some(meta.value()?.parse()?);
+                        return Ok(());
+                    }
+
+                    let lit = meta.value()?.parse::<LitStr>()?;
+                    if lit.value() != "error" {
+                        return Err(syn::Error::new(lit.span(), "expected `\"error\"`"));
+                    }
+
+                    field.conflict = Some(meta.path.span());
+                    return Ok(());
+                }
+
+                Err(meta.error(match derive {
+                    Derive::Merge | Derive::Merge3 => {
+                        "unknown field, expected either `skip`, `strategy`, `deep`, `default`, `partial`, `three_way`"
+                    }
+
+                    Derive::TryMerge => {
+                        "unknown field, expected either `skip`, `strategy`, `deep`, `default`, `partial`, `three_way`, `try_strategy`, `conflict`"
+                    }
+                }))
+            })?;
+        }
+
+        if let (Some(span), Some(_)) = (field.deep, &field.strategy) {
+            return Err(syn::Error::new(
+                span,
+                "`#[merge(deep)]` and `#[merge(strategy)]` can't be used on the same field",
+            ));
+        }
+
+        if let Some((span, _)) = field.default
+            && (field.strategy.is_some() || field.deep.is_some())
+        {
+            return Err(syn::Error::new(
+                span,
+                "`#[merge(default)]` can't be used with either `#[merge(strategy)]` or `#[merge(deep)]`",
+            ));
+        }
+
+        if field.try_strategy.is_some() || field.conflict.is_some() {
+            let span = field
+                .conflict
+                .or_else(|| field.try_strategy.as_ref().map(Spanned::span))
+                .unwrap();
+
+            if field.strategy.is_some() || field.deep.is_some() || field.default.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "`#[merge(try_strategy)]` and `#[merge(conflict)]` can't be used with either `#[merge(strategy)]`, `#[merge(deep)]` or `#[merge(default)]`",
+                ));
+            }
+        }
+
+        Ok(field)
+    }
+
+    /// Resolves how this field is merged by `body`. Field-level attributes always take
+    /// precedence over `#[merge(strategy_for)]`, then the container's `#[merge(strategy)]`
+    /// and lastly the container's `#[merge(default)]`, which only applies to [scalar](is_scalar)
+    /// fields so that nested structs and collections are still merged recursively.
+    fn resolve<'a>(&'a self, container: &'a Container, body: Body, ty: &Type) -> Resolved<'a> {
+        if self.skipped {
+            return Resolved::Skip;
+        }
+
+        if body == Body::Merge3 {
+            return self
+                .three_way
+                .as_ref()
+                .map(Resolved::Strategy)
+                .unwrap_or(Resolved::Trait);
+        }
+
+        if let Some(strategy) = &self.strategy {
+            return Resolved::Strategy(strategy);
+        }
+
+        if self.deep.is_some() {
+            return Resolved::Deep;
+        }
+
+        if let Some((_, mode)) = self.default {
+            return Resolved::Default(mode);
+        }
+
+        if body == Body::TryMerge {
+            if let Some(strategy) = &self.try_strategy {
+                return Resolved::TryStrategy(strategy);
+            }
+
+            if let Some(span) = self.conflict {
+                return Resolved::Conflict(span);
+            }
+        }
+
+        if let Some(strategy) = container.strategy_for(ty) {
+            return Resolved::Strategy(strategy);
+        }
+
+        if container.recurse_options.is_some()
+            && matches!(body, Body::Merge | Body::Report | Body::TryMerge)
+            && is_option(ty)
+        {
+            return Resolved::RecurseOption;
+        }
+
+        if let Some(strategy) = &container.strategy {
+            return Resolved::Strategy(strategy);
+        }
+
+        if let Some(mode) = container.default
+            && is_scalar(ty)
+        {
+            return Resolved::Default(mode);
+        }
+
+        Resolved::Trait
+    }
+
+    /// Expands how `lhs` (a `&mut T`) and `rhs` (a `T`) are merged, or `None` if
+    /// the field was skipped. `segment` is the name of the field that is used for
+    /// the path of a `MergeConflict` or within the `MergeReport`.
+    fn expand(
+        &self,
+        container: &Container,
+        body: Body,
+        (span, ty): (Span, &Type),
+        (lhs, rhs): (TokenStream, TokenStream),
+        segment: &str,
+    ) -> Option<TokenStream> {
+        let krate = &container.krate;
+        let call = match (self.resolve(container, body, ty), body) {
+            (Resolved::Skip, _) => return None,
+
+            // strategies can't be observed, so the field is compared to a snapshot of itself if
+            // its type allows it, see `strategy::__report`
+            (Resolved::Strategy(strategy), Body::Report) => {
+                return Some(quote_spanned! {strategy.span()=> {
+                    // only one of them is used depending on the field's type
+                    #[allow(unused_imports)]
+                    use #krate::merge::strategy::__report::{Comparable as _, Opaque as _};
+
+                    report.enter(#segment);
+                    let probe = #krate::merge::strategy::__report::Probe::<#ty>(::core::marker::PhantomData);
+                    let before = (&probe).snapshot(#lhs);
+                    #strategy(#lhs, #rhs);
+                    if (&probe).changed(before, #lhs) {
+                        report.record();
+                    }
+
+                    report.leave();
+                }});
+            }
+
+            (Resolved::Deep, Body::Report) => {
+                return Some(quote_spanned! {span=> {
+                    report.enter(#segment);
+                    #krate::merge::strategy::maps::__deep_with_report(#lhs, #rhs, report);
+                    report.leave();
+                }});
+            }
+
+            // `base` is expanded by the caller as the second operand of `rhs` for `Merge3`
+            (Resolved::Strategy(strategy), _) => return Some(quote_spanned!(strategy.span()=> #strategy(#lhs, #rhs))),
+            (Resolved::Deep, _) => {
+                return Some(quote_spanned!(span=> #krate::merge::strategy::maps::deep(#lhs, #rhs)));
+            }
+
+            (Resolved::Default(mode), Body::Report) => {
+                let method = match mode {
+                    DefaultMode::Overwrite => quote!(merge_default_with_report),
+                    DefaultMode::Fill => quote!(fill_default_with_report),
+                };
+
+                return Some(quote_spanned! {span=> {
+                    report.enter(#segment);
+                    #krate::merge::MergeDefault::#method(#lhs, #rhs, report);
+                    report.leave();
+                }});
+            }
+
+            (Resolved::Default(mode), _) => {
+                let strategy = mode.to_strategy(krate);
+                return Some(quote_spanned!(span=> #strategy(#lhs, #rhs)));
+            }
+
+            (Resolved::RecurseOption, Body::Report) => {
+                return Some(quote_spanned! {span=> {
+                    report.enter(#segment);
+                    #krate::merge::strategy::option::__recurse_with_report(#lhs, #rhs, report);
+                    report.leave();
+                }});
+            }
+
+            (Resolved::RecurseOption, Body::TryMerge) => {
+                quote_spanned!(span=> #krate::merge::strategy::option::try_recurse(#lhs, #rhs))
+            }
+
+            (Resolved::RecurseOption, _) => {
+                return Some(quote_spanned!(span=> #krate::merge::strategy::option::recurse(#lhs, #rhs)));
+            }
+
+            (Resolved::Trait, Body::Merge) => {
+                return Some(quote_spanned!(span=> #krate::merge::Merge::merge(#lhs, #rhs)));
+            }
+
+            (Resolved::Trait, Body::Report) => {
+                return Some(quote_spanned! {span=> {
+                    report.enter(#segment);
+                    #krate::merge::Merge::merge_with_report(#lhs, #rhs, report);
+                    report.leave();
+                }});
+            }
+
+            (Resolved::Trait, Body::Merge3) => {
+                return Some(quote_spanned!(span=> #krate::merge::Merge3::merge3(#lhs, #rhs)));
+            }
+
+            (Resolved::Trait, Body::TryMerge) => {
+                quote_spanned!(span=> #krate::merge::TryMerge::try_merge(#lhs, #rhs))
+            }
+
+            (Resolved::TryStrategy(strategy), _) => quote_spanned!(strategy.span()=> #strategy(#lhs, #rhs)),
+            (Resolved::Conflict(span), _) => {
+                quote_spanned!(span=> #krate::merge::strategy::conflict::error(#lhs, #rhs))
+            }
+        };
+
+        Some(quote! {
+            ::core::result::Result::map_err(#call, |conflict| conflict.within(#segment))?
+        })
+    }
+}
+
+/// How a field is merged, see [`Field::resolve`].
+enum Resolved<'a> {
+    Skip,
+    Strategy(&'a Strategy),
+    Deep,
+    Default(DefaultMode),
+    TryStrategy(&'a Strategy),
+    Conflict(Span),
+
+    /// `strategy::option::recurse` from `#[merge(recurse_options)]`, which requires `T: Merge`, or
+    /// `strategy::option::try_recurse` for `TryMerge`.
+    RecurseOption,
+
+    /// The trait that is being derived.
+    Trait,
+}
+
+/// Which trait is being derived, `Merge`, `TryMerge` and `Merge3` share the same attributes.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Derive {
+    Merge,
+    TryMerge,
+    Merge3,
+}
+
+/// Which method's body is being expanded.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Body {
+    /// `Merge::merge`
+    Merge,
+
+    /// `Merge::merge_with_report`, which has a `report: &mut MergeReport` in scope.
+    Report,
+
+    /// `TryMerge::try_merge`
+    TryMerge,
+
+    /// `Merge3::merge3`, where `rhs` is expanded as `base, theirs`.
+    Merge3,
+}
+
+impl Derive {
+    /// Wraps the bodies of `merge` and `merge_with_report`, or `try_merge`, into the trait
+    /// implementation. `diverges` is `true` if the bodies never return, like in enumerations
+    /// without any variants.
+    ///
+    /// `bounded` are the types of the fields that are merged with the trait itself, which is
+    /// used to infer the bounds of the type parameters.
+    fn implement<'a>(
+        self,
+        container: &Container,
+        input: &DeriveInput,
+        bounded: impl IntoIterator<Item = &'a Type>,
+        body: impl Fn(Body) -> TokenStream,
+        diverges: bool,
+    ) -> TokenStream {
+        let krate = &container.krate;
+        let ident = &input.ident;
+        let trait_ = match self {
+            Derive::Merge => quote!(#krate::merge::Merge),
+            Derive::TryMerge => quote!(#krate::merge::TryMerge),
+            Derive::Merge3 => quote!(#krate::merge::Merge3),
+        };
+
+        let generics = container.generics(&input.generics, trait_, bounded);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+        match self {
+            Derive::Merge => {
+                let merge = body(Body::Merge);
+                let report = body(Body::Report);
+
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #krate::merge::Merge for #ident #ty_generics #where_clause {
+                        fn merge(&mut self, other: Self) {
+                            #merge
+                        }
+
+                        #krate::merge::__with_report! {
+                            #[allow(unused_variables)]
+                            fn merge_with_report(&mut self, other: Self, report: &mut #krate::merge::MergeReport) {
+                                #report
+                            }
+                        }
+                    }
+
+                    #krate::merge::__with_try_merge! {
+                        #[automatically_derived]
+                        impl #impl_generics #krate::merge::TryMerge for #ident #ty_generics #where_clause {
+                            fn try_merge(&mut self, other: Self) -> ::core::result::Result<(), #krate::merge::MergeConflict> {
+                                #krate::merge::Merge::merge(self, other);
+                                ::core::result::Result::Ok(())
+                            }
+                        }
+                    }
+                }
+            }
+
+            Derive::Merge3 => {
+                let body = body(Body::Merge3);
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #krate::merge::Merge3 for #ident #ty_generics #where_clause {
+                        #[allow(unused_variables)]
+                        fn merge3(&mut self, base: Self, theirs: Self) {
+                            #body
+                        }
+                    }
+                }
+            }
+
+            Derive::TryMerge => {
+                let body = body(Body::TryMerge);
+                let ok = (!diverges).then(|| quote!(::core::result::Result::Ok(())));
+
+                quote! {
+                    #[automatically_derived]
+                    impl #impl_generics #krate::merge::TryMerge for #ident #ty_generics #where_clause {
+                        fn try_merge(&mut self, other: Self) -> ::core::result::Result<(), #krate::merge::MergeConflict> {
+                            #body
+                            #ok
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the bodies that should be expanded for this trait.
+    fn bodies(self) -> &'static [Body] {
+        match self {
+            Derive::Merge => &[Body::Merge, Body::Report],
+            Derive::TryMerge => &[Body::TryMerge],
+            Derive::Merge3 => &[Body::Merge3],
+        }
+    }
+}
+
+/// Returns `true` if `field` is merged with the trait that is being derived by any of its bodies.
+fn uses_trait(field: &Field, container: &Container, derive: Derive, ty: &Type) -> bool {
+    derive.bodies().iter().any(|body| {
+        matches!(
+            field.resolve(container, *body, ty),
+            Resolved::Trait | Resolved::RecurseOption
+        )
+    })
+}
+
+/// Returns `true` if `ty` is an `Option<T>`, which is only known from the name of the type.
+pub(crate) fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option" && !segment.arguments.is_none()),
+
+        _ => false,
+    }
+}
+
+/// Returns `true` if `ty` is a primitive, a `String` or a `PathBuf`, or an `Option` of one. These
+/// are replaced as a whole by their `Merge` implementation anyway, unlike structs or collections.
+fn is_scalar(ty: &Type) -> bool {
+    const SCALARS: &[&str] = &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+        "usize", "String", "PathBuf",
+    ];
+
+    let Type::Path(path) = ty else {
+        return false;
+    };
+
+    let Some(segment) = path.path.segments.last().filter(|_| path.qself.is_none()) else {
+        return false;
+    };
+
+    match &segment.arguments {
+        PathArguments::None => SCALARS.iter().any(|scalar| segment.ident == scalar),
+        PathArguments::AngleBracketed(args) if segment.ident == "Option" && args.args.len() == 1 => {
+            matches!(&args.args[0], GenericArgument::Type(inner) if is_scalar(inner))
+        }
+
+        _ => false,
+    }
+}
+
+/// Returns the name of a field that is used in the path of a `MergeConflict` or `MergeReport`.
+fn segment(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => {
+            let name = ident.to_string();
+            name.strip_prefix("r#").map(ToOwned::to_owned).unwrap_or(name)
+        }
+
+        Member::Unnamed(index) => index.index.to_string(),
+    }
 }
 
 struct StructField {
     attrs: Vec<Attribute>,
     member: Member,
     span: Span,
+    ty: Type,
 }
 
 impl From<(usize, syn::Field)> for StructField {
@@ -110,6 +910,7 @@ impl From<(usize, syn::Field)> for StructField {
         let span = field.span();
         Self {
             attrs: field.attrs,
+            ty: field.ty,
             span,
             member: field
                 .ident
@@ -120,117 +921,325 @@ impl From<(usize, syn::Field)> for StructField {
     }
 }
 
-pub fn expand_struct(
-    DeriveInput {
-        ident, generics, attrs, ..
-    }: &DeriveInput,
-    fields: &Fields,
-) -> TokenStream {
-    let mut container = Container::default();
-    for attr in attrs {
-        if !attr.path().is_ident("merge") {
-            continue;
-        }
+pub fn expand_struct(input: &DeriveInput, fields: &Fields, derive: Derive) -> TokenStream {
+    let container = match Container::from_attrs(&input.attrs) {
+        Ok(container) => container,
+        Err(e) => return e.into_compile_error(),
+    };
 
-        if let Meta::List(list) = &attr.meta
-            && list.tokens.is_empty() {
-                continue;
-            }
+    if let Some(options) = &container.partial
+        && derive == Derive::TryMerge
+    {
+        return syn::Error::new(
+            options.span,
+            "`#[merge(partial)]` is only supported by `#[derive(Merge)]`",
+        )
+        .into_compile_error();
+    }
 
-        if let Err(e) = attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("crate") {
-                container.krate = meta.value()?.parse()?;
-                return Ok(());
+    let mut parsed = Vec::with_capacity(fields.len());
+    for s_field in fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| StructField::from((idx, field.clone())))
+    {
+        match Field::from_attrs(&s_field.attrs, derive) {
+            Ok(field) => {
+                if let Some(span) = field.partial
+                    && container.partial.is_none()
+                {
+                    return syn::Error::new(
+                        span,
+                        "`#[merge(partial)]` on a field requires `#[merge(partial)]` on the container",
+                    )
+                    .into_compile_error();
+                }
+
+                parsed.push((s_field, field));
             }
 
-            Err(meta.error("only `crate = <path>` is supported"))
-        }) {
-            return e.into_compile_error();
+            Err(e) => return e.into_compile_error(),
         }
     }
 
-    let krate = &container.krate;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    // `Merge3` and `Merge` share the same attributes, so the partial is only generated once
+    let partial = match &container.partial {
+        Some(options) if derive == Derive::Merge => {
+            match partial::expand(input, &container, options, fields, &parsed) {
+                Ok(partial) => partial,
+                Err(e) => return e.into_compile_error(),
+            }
+        }
+
+        _ => TokenStream::new(),
+    };
+
     if fields.is_empty() {
-        return quote! {
-            #[automatically_derived]
-            impl #impl_generics #krate::merge::Merge for #ident #ty_generics #where_clause {
-                fn merge(&mut self, other: Self) {
-                    let _ = other;
+        let implementation = derive.implement(
+            &container,
+            input,
+            [],
+            |body| match body {
+                Body::Merge3 => TokenStream::new(),
+                _ => quote!(let _ = other;),
+            },
+            false,
+        );
+
+        return quote!(#implementation #partial);
+    }
+
+    let body = |body: Body| {
+        let assignments = parsed.iter().filter_map(|(s_field, field)| {
+            let name = &s_field.member;
+            let rhs = match body {
+                Body::Merge3 => quote!(base.#name, theirs.#name),
+                _ => quote!(other.#name),
+            };
+
+            field.expand(
+                &container,
+                body,
+                (s_field.span, &s_field.ty),
+                (quote!(&mut self.#name), rhs),
+                &segment(name),
+            )
+        });
+
+        quote!(#(#assignments;)*)
+    };
+
+    let bounded = parsed
+        .iter()
+        .filter(|(s_field, field)| uses_trait(field, &container, derive, &s_field.ty))
+        .map(|(s_field, _)| &s_field.ty);
+
+    let implementation = derive.implement(&container, input, bounded, body, false);
+    quote!(#implementation #partial)
+}
+
+/// Expands `Merge` for enumerations: if both values are the same variant, then their fields are
+/// merged like a struct's, otherwise `self` is replaced with `other`.
+pub fn expand_enumeration(input: &DeriveInput, data: &DataEnum, derive: Derive) -> TokenStream {
+    let container = match Container::from_attrs(&input.attrs) {
+        Ok(container) => container,
+        Err(e) => return e.into_compile_error(),
+    };
+
+    if let Some(options) = &container.partial {
+        return syn::Error::new(options.span, "`#[merge(partial)]` is only supported for structs").into_compile_error();
+    }
+
+    if derive == Derive::Merge3 {
+        return expand_enumeration_three_way(input, &container, data);
+    }
+
+    if let Some(path) = &container.strategy {
+        let krate = &container.krate;
+        let body = |body: Body| match body {
+            // like the strategies of fields, the value is recorded if it can be compared with a
+            // snapshot of itself, see `strategy::__report`
+            Body::Report => quote_spanned! {path.span()=>
+                #[allow(unused_imports)]
+                use #krate::merge::strategy::__report::{Comparable as _, Opaque as _};
+
+                let probe = #krate::merge::strategy::__report::Probe::<Self>(::core::marker::PhantomData);
+                let before = (&probe).snapshot(self);
+                #path(self, other);
+                if (&probe).changed(before, self) {
+                    report.record();
                 }
-            }
+            },
+
+            _ => quote_spanned!(path.span()=> #path(self, other);),
         };
+
+        return derive.implement(&container, input, [], body, false);
     }
 
-    let mut assignments = Vec::with_capacity(fields.len());
-    for s_field in fields
-        .iter()
-        .enumerate()
-        .map(|(idx, field)| StructField::from((idx, field.clone())))
-    {
-        let mut field = Field::default();
-        for attr in s_field.attrs {
-            if !attr.path().is_ident("merge") {
-                continue;
-            }
+    if data.variants.is_empty() {
+        return derive.implement(&container, input, [], |_| quote!(match other {}), true);
+    }
 
-            if let Meta::List(list) = &attr.meta
-                && list.tokens.is_empty() {
-                    continue;
+    let mut bodies = Vec::with_capacity(2);
+    let mut bounded = Vec::new();
+    for &body in derive.bodies() {
+        let mut arms = Vec::with_capacity(data.variants.len());
+        for variant in &data.variants {
+            let name = &variant.ident;
+            let mut lhs = Vec::with_capacity(variant.fields.len());
+            let mut rhs = Vec::with_capacity(variant.fields.len());
+            let mut assignments = Vec::with_capacity(variant.fields.len());
+
+            for (idx, field) in variant.fields.iter().enumerate() {
+                let attrs = match Field::from_attrs(&field.attrs, derive) {
+                    Ok(attrs) => attrs,
+                    Err(e) => return e.into_compile_error(),
+                };
+
+                if let Some(span) = attrs.partial {
+                    return syn::Error::new(span, "`#[merge(partial)]` is only supported for structs")
+                        .into_compile_error();
                 }
 
-            if let Err(e) = attr.parse_nested_meta(|meta| {
-                if meta.path.is_ident("skip") {
-                    if field.skipped {
-                        return Err(syn::Error::new(meta.path.span(), "field already has `#[merge(skip)]`"));
-                    }
+                let member = field
+                    .ident
+                    .clone()
+                    .map(Member::Named)
+                    .unwrap_or(Member::Unnamed(idx.into()));
+                let (l, r) = (format_ident!("__self_{}", idx), format_ident!("__other_{}", idx));
 
-                    field.skipped = true;
-                    return Ok(());
+                if uses_trait(&attrs, &container, derive, &field.ty) {
+                    bounded.push(&field.ty);
                 }
 
-                if meta.path.is_ident("strategy") {
-                    let input = meta.value()?;
-                    if field.strategy.is_some() {
-                        return Err(syn::Error::new(
-                            meta.path.span(),
-                            "field already has `#[merge(strategy)]`",
-                        ));
+                match attrs.expand(
+                    &container,
+                    body,
+                    (field.span(), &field.ty),
+                    (quote!(#l), quote!(#r)),
+                    &segment(&member),
+                ) {
+                    Some(assignment) => {
+                        lhs.push(quote!(#member: #l));
+                        rhs.push(quote!(#member: #r));
+                        assignments.push(assignment);
                     }
 
-                    field.strategy = Some(input.parse()?);
-                    return Ok(());
+                    None => {
+                        lhs.push(quote!(#member: _));
+                        rhs.push(quote!(#member: _));
+                    }
                 }
-
-                Err(meta.error("unknown field, expected either `skip`, `strategy`"))
-            }) {
-                return e.into_compile_error();
             }
-        }
 
-        if field.skipped {
-            continue;
+            arms.push(quote! {
+                (Self::#name { #(#lhs,)* }, Self::#name { #(#rhs,)* }) => {
+                    #(#assignments;)*
+                }
+            });
         }
 
-        let name = &s_field.member;
-        assignments.push(match field.strategy {
-            Some(path) => quote_spanned!(path.span()=> #path(&mut self.#name, other.#name)),
-            None => quote_spanned!(s_field.span=> #krate::merge::Merge::merge(&mut self.#name, other.#name)),
-        });
+        // a different variant replaces the whole value, which is recorded as a change
+        let record = (body == Body::Report).then(|| quote!(report.record();));
+        bodies.push((
+            body,
+            quote! {
+                match (self, other) {
+                    #(#arms)*
+
+                    #[allow(unreachable_patterns)]
+                    (this, other) => {
+                        *this = other;
+                        #record
+                    }
+                }
+            },
+        ));
     }
 
-    quote! {
-        #[automatically_derived]
-        impl #impl_generics #krate::merge::Merge for #ident #ty_generics #where_clause {
-            fn merge(&mut self, other: Self) {
-                #(#assignments;)*
+    let body = |body: Body| {
+        bodies
+            .iter()
+            .find(|(b, _)| *b == body)
+            .map(|(_, tokens)| tokens.clone())
+            .unwrap_or_default()
+    };
+
+    derive.implement(&container, input, bounded, body, false)
+}
+
+/// Expands `Merge3` for enumerations: if `theirs` changed the variant from `base`, then `self`
+/// is replaced with `theirs`. Otherwise, the fields are merged like a struct's if all three
+/// values are the same variant, or `self` is kept since only it changed the variant.
+fn expand_enumeration_three_way(input: &DeriveInput, container: &Container, data: &DataEnum) -> TokenStream {
+    if data.variants.is_empty() {
+        return Derive::Merge3.implement(container, input, [], |_| quote!(match theirs {}), true);
+    }
+
+    let mut arms = Vec::with_capacity(data.variants.len());
+    let mut bounded = Vec::new();
+    for variant in &data.variants {
+        let name = &variant.ident;
+        let mut ours = Vec::with_capacity(variant.fields.len());
+        let mut base = Vec::with_capacity(variant.fields.len());
+        let mut theirs = Vec::with_capacity(variant.fields.len());
+        let mut assignments = Vec::with_capacity(variant.fields.len());
+
+        for (idx, field) in variant.fields.iter().enumerate() {
+            let attrs = match Field::from_attrs(&field.attrs, Derive::Merge3) {
+                Ok(attrs) => attrs,
+                Err(e) => return e.into_compile_error(),
+            };
+
+            if let Some(span) = attrs.partial {
+                return syn::Error::new(span, "`#[merge(partial)]` is only supported for structs").into_compile_error();
+            }
+
+            let member = field
+                .ident
+                .clone()
+                .map(Member::Named)
+                .unwrap_or(Member::Unnamed(idx.into()));
+
+            let (o, b, t) = (
+                format_ident!("__ours_{}", idx),
+                format_ident!("__base_{}", idx),
+                format_ident!("__theirs_{}", idx),
+            );
+
+            if uses_trait(&attrs, container, Derive::Merge3, &field.ty) {
+                bounded.push(&field.ty);
+            }
+
+            match attrs.expand(
+                container,
+                Body::Merge3,
+                (field.span(), &field.ty),
+                (quote!(#o), quote!(#b, #t)),
+                &segment(&member),
+            ) {
+                Some(assignment) => {
+                    ours.push(quote!(#member: #o));
+                    base.push(quote!(#member: #b));
+                    theirs.push(quote!(#member: #t));
+                    assignments.push(assignment);
+                }
+
+                None => {
+                    ours.push(quote!(#member: _));
+                    base.push(quote!(#member: _));
+                    theirs.push(quote!(#member: _));
+                }
             }
         }
+
+        arms.push(quote! {
+            (Self::#name { #(#ours,)* }, Self::#name { #(#base,)* }, Self::#name { #(#theirs,)* }) => {
+                #(#assignments;)*
+            }
+        });
     }
-}
 
-#[allow(unused)]
-pub fn expand_enumeration() -> TokenStream {
-    // TODO(@auguwu): experiment on how to expand `Merge` for `enum`s.
-    todo!()
+    Derive::Merge3.implement(
+        container,
+        input,
+        bounded,
+        |_| {
+            quote! {
+                if ::core::mem::discriminant(&base) != ::core::mem::discriminant(&theirs) {
+                    *self = theirs;
+                    return;
+                }
+
+                match (self, base, theirs) {
+                    #(#arms)*
+
+                    #[allow(unreachable_patterns)]
+                    _ => {}
+                }
+            }
+        },
+        false,
+    )
 }
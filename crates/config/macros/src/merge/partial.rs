@@ -0,0 +1,278 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Expansion of `#[merge(partial)]`, which generates a sparse overlay of a struct
+//! and implements `Partial` for it.
+
+use super::{Container, Field, StructField, is_option};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    Attribute, DeriveInput, Fields, Ident, LitStr, Member, Token, meta::ParseNestedMeta, spanned::Spanned, token,
+};
+
+/// **#\[merge(partial)\]** | **#\[merge(partial(name = ..., derive(...)))\]**
+pub struct Options {
+    pub span: Span,
+
+    /// **name = ...**: the name of the generated struct, defaults to `Partial<Name>`.
+    pub name: Option<Ident>,
+
+    /// **derive(...)**: extra traits to derive on the generated struct.
+    pub derives: Vec<syn::Path>,
+}
+
+impl Options {
+    pub fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+        let mut options = Options {
+            span: meta.path.span(),
+            name: None,
+            derives: Vec::new(),
+        };
+
+        if !meta.input.peek(token::Paren) {
+            return Ok(options);
+        }
+
+        meta.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let input = meta.value()?;
+                options.name = Some(if input.peek(LitStr) {
+                    input.parse::<LitStr>()?.parse()?
+                } else {
+                    input.parse()?
+                });
+
+                return Ok(());
+            }
+
+            if meta.path.is_ident("derive") {
+                return meta.parse_nested_meta(|meta| {
+                    options.derives.push(meta.path);
+                    Ok(())
+                });
+            }
+
+            Err(meta.error("unknown field, expected either `name`, `derive`"))
+        })?;
+
+        Ok(options)
+    }
+}
+
+/// serde attributes on the container that are kept on the generated struct.
+const CONTAINER_SERDE_ATTRS: &[&str] = &["rename_all", "deny_unknown_fields"];
+
+/// serde attributes on fields that are kept on the generated struct.
+const FIELD_SERDE_ATTRS: &[&str] = &["rename", "alias"];
+
+pub fn expand(
+    input: &DeriveInput,
+    container: &Container,
+    options: &Options,
+    fields: &Fields,
+    parsed: &[(StructField, Field)],
+) -> syn::Result<TokenStream> {
+    let krate = &container.krate;
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let name = options
+        .name
+        .clone()
+        .unwrap_or_else(|| format_ident!("Partial{}", ident, span = options.span));
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics = &input.generics;
+
+    let mut definitions = Vec::with_capacity(parsed.len());
+    let mut defaults = Vec::with_capacity(parsed.len());
+    let mut applies = Vec::with_capacity(parsed.len());
+    let mut merges = Vec::with_capacity(parsed.len());
+
+    for (field, (s_field, attrs)) in fields.iter().zip(parsed).filter(|(_, (_, attrs))| !attrs.skipped) {
+        let member = &s_field.member;
+        let partial_member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(definitions.len().into()),
+        };
+
+        let docs = field.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+        let serde = serde_attrs(&field.attrs, FIELD_SERDE_ATTRS)?;
+        let field_vis = &field.vis;
+        let ty = &field.ty;
+        let colon = field.ident.as_ref().map(|ident| quote!(#ident:));
+
+        if attrs.partial.is_some() {
+            definitions.push(quote! {
+                #(#docs)*
+                #serde
+                #field_vis #colon <#ty as #krate::merge::Partial>::Partial
+            });
+
+            defaults.push(quote!(#partial_member: ::core::default::Default::default()));
+            applies.push(quote_spanned! {s_field.span=>
+                #krate::merge::Partial::apply(&mut self.#member, partial.#partial_member);
+            });
+
+            merges.push(quote_spanned! {s_field.span=>
+                #krate::merge::Merge::merge(&mut self.#partial_member, other.#partial_member);
+            });
+
+            continue;
+        }
+
+        let (ty, apply) = if is_option(ty) {
+            (quote!(#ty), quote!(::core::option::Option::Some(value)))
+        } else {
+            (quote!(::core::option::Option<#ty>), quote!(value))
+        };
+
+        definitions.push(quote! {
+            #(#docs)*
+            #serde
+            #field_vis #colon #ty
+        });
+
+        defaults.push(quote!(#partial_member: ::core::option::Option::None));
+        applies.push(quote! {
+            if let ::core::option::Option::Some(value) = partial.#partial_member {
+                self.#member = #apply;
+            }
+        });
+
+        merges.push(quote! {
+            if other.#partial_member.is_some() {
+                self.#partial_member = other.#partial_member;
+            }
+        });
+    }
+
+    let body = match fields {
+        Fields::Named(_) => quote!(#where_clause { #(#definitions,)* }),
+        Fields::Unnamed(_) => quote!(( #(#definitions,)* ) #where_clause;),
+        Fields::Unit => quote!(#where_clause;),
+    };
+
+    let default = match fields {
+        Fields::Unit => quote!(#name),
+        _ => quote!(#name { #(#defaults,)* }),
+    };
+
+    let serde = if cfg!(feature = "serde") {
+        let path = quote!(#krate::merge::__private::serde).to_string();
+        let attrs = serde_attrs(&input.attrs, CONTAINER_SERDE_ATTRS)?;
+
+        quote! {
+            #[derive(#krate::merge::__private::serde::Deserialize)]
+            #[serde(crate = #path, default)]
+            #attrs
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let derives = (!options.derives.is_empty()).then(|| {
+        let derives = &options.derives;
+        quote!(#[derive(#(#derives),*)])
+    });
+
+    let doc = format!(" A sparse overlay of [`{ident}`] where every field is optional.");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #derives
+        #serde
+        #vis struct #name #generics #body
+
+        #[automatically_derived]
+        impl #impl_generics ::core::default::Default for #name #ty_generics #where_clause {
+            fn default() -> Self {
+                #default
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #krate::merge::Merge for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn merge(&mut self, other: Self) {
+                #(#merges)*
+            }
+        }
+
+        #krate::merge::__with_try_merge! {
+            #[automatically_derived]
+            impl #impl_generics #krate::merge::TryMerge for #name #ty_generics #where_clause {
+                fn try_merge(&mut self, other: Self) -> ::core::result::Result<(), #krate::merge::MergeConflict> {
+                    #krate::merge::Merge::merge(self, other);
+                    ::core::result::Result::Ok(())
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #krate::merge::Partial for #ident #ty_generics #where_clause {
+            type Partial = #name #ty_generics;
+
+            #[allow(unused_variables)]
+            fn apply(&mut self, partial: Self::Partial) {
+                #(#applies)*
+            }
+        }
+    })
+}
+
+/// Collects the `#[serde(...)]` attributes in `attrs` that are in `allowed`, which is
+/// empty if the `serde` feature is disabled.
+fn serde_attrs(attrs: &[Attribute], allowed: &[&str]) -> syn::Result<TokenStream> {
+    if !cfg!(feature = "serde") {
+        return Ok(TokenStream::new());
+    }
+
+    let mut kept = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        attr.parse_nested_meta(|meta| {
+            let path = meta.path.clone();
+            let keep = allowed.iter().any(|key| path.is_ident(key));
+
+            if meta.input.peek(Token![=]) {
+                let value = meta.value()?.parse::<syn::Expr>()?;
+                if keep {
+                    kept.push(quote!(#path = #value));
+                }
+            } else if meta.input.peek(token::Paren) {
+                let group = meta.input.parse::<proc_macro2::Group>()?;
+                if keep {
+                    kept.push(quote!(#path #group));
+                }
+            } else if keep {
+                kept.push(quote!(#path));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if kept.is_empty() {
+        return Ok(TokenStream::new());
+    }
+
+    Ok(quote!(#[serde(#(#kept),*)]))
+}
@@ -0,0 +1,563 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::merge::Path;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::{
+    Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Generics, Lit, LitStr, Meta, MetaNameValue, Token,
+    parse_quote, spanned::Spanned, token,
+};
+
+/// Casing rules for `#[schema(rename_all = "...")]`, these are the same as `serde`'s.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_lit(lit: &LitStr) -> syn::Result<Self> {
+        Ok(match &*lit.value() {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "unknown casing rule, expected one of `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`, `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case`, `SCREAMING-KEBAB-CASE`",
+                ));
+            }
+        })
+    }
+
+    /// Applies the rule to a name by splitting it into words, this works for both
+    /// `snake_case` field names and `PascalCase` variant names.
+    fn apply(self, name: &str) -> String {
+        let mut words = Vec::<String>::new();
+        for part in name.split('_').filter(|s| !s.is_empty()) {
+            let mut word = String::new();
+            for ch in part.chars() {
+                if ch.is_uppercase() && !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+
+                word.push(ch);
+            }
+
+            words.push(word);
+        }
+
+        let capitalize = |word: &String| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| {
+                    c.to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect::<String>()
+                })
+                .unwrap_or_default()
+        };
+
+        match self {
+            RenameRule::Lower => words.concat().to_lowercase(),
+            RenameRule::Upper => words.concat().to_uppercase(),
+            RenameRule::Pascal => words.iter().map(capitalize).collect(),
+            RenameRule::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(idx, w)| if idx == 0 { w.to_lowercase() } else { capitalize(w) })
+                .collect(),
+
+            RenameRule::Snake => words.join("_").to_lowercase(),
+            RenameRule::ScreamingSnake => words.join("_").to_uppercase(),
+            RenameRule::Kebab => words.join("-").to_lowercase(),
+            RenameRule::ScreamingKebab => words.join("-").to_uppercase(),
+        }
+    }
+}
+
+struct Container {
+    krate: Path,
+    default: bool,
+    serde_default: bool,
+    rename_all: Option<RenameRule>,
+}
+
+impl Container {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut container = Container {
+            krate: crate::merge::Container::default().krate,
+            default: false,
+            serde_default: serde_default(attrs),
+            rename_all: None,
+        };
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("schema")) {
+            if let Meta::List(list) = &attr.meta
+                && list.tokens.is_empty()
+            {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    container.krate = meta.value()?.parse()?;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("default") {
+                    container.default = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("rename_all") {
+                    container.rename_all = Some(RenameRule::from_lit(&meta.value()?.parse()?)?);
+                    return Ok(());
+                }
+
+                Err(meta.error("unknown field, expected either `crate`, `default`, `rename_all`"))
+            })?;
+        }
+
+        if container.rename_all.is_none() {
+            container.rename_all = SerdeRename::from_attrs(attrs)?.rename_all;
+        }
+
+        Ok(container)
+    }
+}
+
+/// **#\[schema\]** for an individual field or variant.
+#[derive(Default)]
+struct Item {
+    skipped: bool,
+    rename: Option<String>,
+    default: Option<Expr>,
+}
+
+impl Item {
+    fn from_attrs(attrs: &[Attribute], allow_default: bool) -> syn::Result<Self> {
+        let mut item = Item::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("schema")) {
+            if let Meta::List(list) = &attr.meta
+                && list.tokens.is_empty()
+            {
+                continue;
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    item.skipped = true;
+                    return Ok(());
+                }
+
+                if meta.path.is_ident("rename") {
+                    item.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    return Ok(());
+                }
+
+                if allow_default && meta.path.is_ident("default") {
+                    item.default = Some(meta.value()?.parse()?);
+                    return Ok(());
+                }
+
+                Err(meta.error(if allow_default {
+                    "unknown field, expected either `skip`, `rename`, `default`"
+                } else {
+                    "unknown field, expected either `skip`, `rename`"
+                }))
+            })?;
+        }
+
+        if item.rename.is_none() {
+            item.rename = SerdeRename::from_attrs(attrs)?.rename;
+        }
+
+        Ok(item)
+    }
+}
+
+/// `#[serde(rename = "...")]` and `#[serde(rename_all = "...")]`, which are only read if the
+/// `serde` feature is enabled. `#[schema]` takes precedence over them, and the name that is
+/// deserialized from is used if `serialize` and `deserialize` are renamed separately.
+#[derive(Default)]
+struct SerdeRename {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+}
+
+impl SerdeRename {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut renames = SerdeRename::default();
+        if !cfg!(feature = "serde") {
+            return Ok(renames);
+        }
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                let rename = meta.path.is_ident("rename");
+                if !rename && !meta.path.is_ident("rename_all") {
+                    // other attributes are skipped, whatever their value is
+                    if meta.input.peek(Token![=]) {
+                        meta.value()?.parse::<Expr>()?;
+                    } else if meta.input.peek(token::Paren) {
+                        meta.input.parse::<proc_macro2::Group>()?;
+                    }
+
+                    return Ok(());
+                }
+
+                let mut name = None;
+                if meta.input.peek(Token![=]) {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                } else {
+                    meta.parse_nested_meta(|nested| {
+                        let value = nested.value()?.parse::<LitStr>()?;
+                        if nested.path.is_ident("deserialize") {
+                            name = Some(value);
+                        }
+
+                        Ok(())
+                    })?;
+                }
+
+                match (name, rename) {
+                    (Some(name), true) => renames.rename = Some(name.value()),
+                    (Some(rule), false) => renames.rename_all = Some(RenameRule::from_lit(&rule)?),
+                    (None, _) => {}
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(renames)
+    }
+}
+
+/// Whether if `#[serde(default)]` or `#[serde(default = "...")]` is in `attrs`. Only the
+/// top-level `default` key is looked for, so that other `#[serde]` attributes don't need
+/// to be parsed.
+fn serde_default(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::List(list) => Some(list.tokens.clone()),
+            _ => None,
+        })
+        .any(|tokens| {
+            let mut at_key = true;
+            tokens.into_iter().any(|tree| match tree {
+                TokenTree::Ident(ident) if at_key && ident == "default" => true,
+                TokenTree::Punct(punct) if punct.as_char() == ',' => {
+                    at_key = true;
+                    false
+                }
+
+                _ => {
+                    at_key = false;
+                    false
+                }
+            })
+        })
+}
+
+/// Collects all `///` doc comments into a single description.
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                value: Expr::Lit(ExprLit { lit: Lit::Str(s), .. }),
+                ..
+            }) if path.is_ident("doc") => Some(s.value()),
+
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).trim_end().to_owned())
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n").trim().to_owned();
+    (!doc.is_empty()).then_some(doc)
+}
+
+fn annotate(krate: &Path, keyword: &str, value: TokenStream) -> TokenStream {
+    quote!(#krate::schema::__private::annotate(&mut schema, #keyword, #value);)
+}
+
+fn describe(krate: &Path, attrs: &[Attribute]) -> TokenStream {
+    match description(attrs) {
+        Some(doc) => annotate(krate, "description", quote!(#krate::schema::Value::from(#doc))),
+        None => TokenStream::new(),
+    }
+}
+
+/// Expands the schema of a set of fields, `defaults` is the identifier of the container's
+/// serialized `Default` implementation if `#[schema(default)]` was used.
+fn expand_fields(container: &Container, fields: &Fields, defaults: Option<&TokenStream>) -> syn::Result<TokenStream> {
+    let krate = &container.krate;
+    match fields {
+        Fields::Unit => Ok(quote!(<() as #krate::schema::JsonSchema>::json_schema())),
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+            let ty = &unnamed.unnamed[0].ty;
+            Ok(quote_spanned!(ty.span()=> __defs.subschema::<#ty>()))
+        }
+
+        Fields::Unnamed(unnamed) => {
+            let items = unnamed
+                .unnamed
+                .iter()
+                .map(|field| {
+                    let ty = &field.ty;
+                    quote_spanned!(ty.span()=> __defs.subschema::<#ty>())
+                })
+                .collect::<Vec<_>>();
+
+            Ok(quote!(#krate::schema::__private::tuple([#(#items),*])))
+        }
+
+        Fields::Named(named) => {
+            let mut properties = Vec::with_capacity(named.named.len());
+            for field in &named.named {
+                let item = Item::from_attrs(&field.attrs, true)?;
+                if item.skipped {
+                    continue;
+                }
+
+                let ident = field.ident.as_ref().unwrap();
+                let name = item.rename.unwrap_or_else(|| {
+                    let name = ident.to_string();
+                    let name = name.strip_prefix("r#").unwrap_or(&name);
+
+                    match container.rename_all {
+                        Some(rule) => rule.apply(name),
+                        None => name.to_owned(),
+                    }
+                });
+
+                let ty = &field.ty;
+                let describe = describe(krate, &field.attrs);
+
+                // fields that have a default value can be omitted
+                let required = match item.default.is_some()
+                    || container.default
+                    || container.serde_default
+                    || serde_default(&field.attrs)
+                {
+                    true => quote!(false),
+                    false => quote!(!<#ty as #krate::schema::JsonSchema>::optional()),
+                };
+
+                let default = match (&item.default, defaults) {
+                    (Some(expr), _) => quote_spanned! {expr.span()=>
+                        if let ::core::result::Result::Ok(value) = #krate::schema::__private::to_value(#expr) {
+                            #krate::schema::__private::annotate(&mut schema, "default", value);
+                        }
+                    },
+
+                    (None, Some(defaults)) => quote! {
+                        if let ::core::option::Option::Some(value) = #defaults.get(#name) {
+                            #krate::schema::__private::annotate(&mut schema, "default", ::core::clone::Clone::clone(value));
+                        }
+                    },
+
+                    (None, None) => TokenStream::new(),
+                };
+
+                properties.push(quote_spanned! {ty.span()=>
+                    (
+                        #name,
+                        {
+                            #[allow(unused_mut)]
+                            let mut schema = __defs.subschema::<#ty>();
+                            #describe
+                            #default
+
+                            schema
+                        },
+                        #required,
+                    )
+                });
+            }
+
+            Ok(quote!(#krate::schema::__private::object([#(#properties),*])))
+        }
+    }
+}
+
+fn add_bounds(krate: &Path, generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    let params = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect::<Vec<_>>();
+
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: #krate::schema::JsonSchema));
+    }
+
+    generics
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let container = Container::from_attrs(&input.attrs)?;
+    let krate = &container.krate;
+    let ident = &input.ident;
+
+    let defaults = quote!(__defaults);
+    let (prelude, body) = match &input.data {
+        Data::Struct(data) => match (&data.fields, container.default) {
+            (Fields::Named(_), true) => (
+                quote! {
+                    let #defaults = #krate::schema::__private::defaults(
+                        #krate::schema::__private::to_value(<Self as ::core::default::Default>::default())
+                    );
+                },
+                expand_fields(&container, &data.fields, Some(&defaults))?,
+            ),
+
+            (fields, _) => (TokenStream::new(), expand_fields(&container, fields, None)?),
+        },
+
+        Data::Enum(data) => {
+            let mut variants = Vec::with_capacity(data.variants.len());
+            let mut unit_only = true;
+            let mut documented = false;
+
+            for variant in &data.variants {
+                let item = Item::from_attrs(&variant.attrs, false)?;
+                if item.skipped {
+                    continue;
+                }
+
+                let name = item.rename.unwrap_or_else(|| match container.rename_all {
+                    Some(rule) => rule.apply(&variant.ident.to_string()),
+                    None => variant.ident.to_string(),
+                });
+
+                unit_only &= matches!(variant.fields, Fields::Unit);
+                documented |= description(&variant.attrs).is_some();
+                variants.push((name, variant));
+            }
+
+            if unit_only && !documented {
+                let names = variants.iter().map(|(name, _)| name);
+                (
+                    TokenStream::new(),
+                    quote!(#krate::schema::__private::enumeration([#(#names),*])),
+                )
+            } else {
+                let mut schemas = Vec::with_capacity(variants.len());
+                for (name, variant) in variants {
+                    let describe = describe(krate, &variant.attrs);
+                    let schema = match &variant.fields {
+                        Fields::Unit => quote!(#krate::schema::__private::constant(#name)),
+                        fields => {
+                            let inner = expand_fields(&container, fields, None)?;
+                            quote!(#krate::schema::__private::tagged(#name, #inner))
+                        }
+                    };
+
+                    schemas.push(quote! {
+                        {
+                            #[allow(unused_mut)]
+                            let mut schema = #schema;
+                            #describe
+
+                            schema
+                        }
+                    });
+                }
+
+                (
+                    TokenStream::new(),
+                    quote!(#krate::schema::__private::one_of([#(#schemas),*])),
+                )
+            }
+        }
+
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "JsonSchema trait for unions will never be supported",
+            ));
+        }
+    };
+
+    // For anything that isn't a struct with named fields, the default value is
+    // attached to the whole schema instead.
+    let default = match (&input.data, container.default) {
+        (Data::Struct(data), true) if matches!(data.fields, Fields::Named(_)) => TokenStream::new(),
+        (_, true) => quote! {
+            if let ::core::result::Result::Ok(value) =
+                #krate::schema::__private::to_value(<Self as ::core::default::Default>::default())
+            {
+                #krate::schema::__private::annotate(&mut schema, "default", value);
+            }
+        },
+
+        (_, false) => TokenStream::new(),
+    };
+
+    let title = ident.to_string();
+    let describe = describe(krate, &input.attrs);
+    let generics = add_bounds(krate, &input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::schema::JsonSchema for #ident #ty_generics #where_clause {
+            fn schema_name() -> ::core::option::Option<#krate::schema::__private::Cow<'static, str>> {
+                ::core::option::Option::Some(#krate::schema::__private::Cow::Borrowed(#title))
+            }
+
+            fn json_schema_in(__defs: &mut #krate::schema::Definitions) -> #krate::schema::Value {
+                #prelude
+
+                #[allow(unused_mut)]
+                let mut schema = #body;
+                #krate::schema::__private::annotate(&mut schema, "title", #krate::schema::Value::from(#title));
+                #describe
+                #default
+
+                schema
+            }
+        }
+    })
+}
@@ -85,15 +85,7 @@
 
 //     let mut error_ty = None;
 //     let mut prefix = None;
-//     let mut krate = syn::Path {
-//         leading_colon: Some(Token![::](Span::call_site())),
-//         segments: [
-//             PathSegment::from(Ident::new("azalia", Span::call_site())),
-//             PathSegment::from(Ident::new("config", Span::call_site())),
-//         ]
-//         .into_iter()
-//         .collect(),
-//     };
+//     let mut krate = crate::krate::resolve();
 
 //     for attr in attrs {
 //         if !attr.path().is_ident("env") {
@@ -20,24 +20,34 @@
 // SOFTWARE.
 
 //! Traits, types, and utilities when dealing with system environment variables.
-
-use std::{
+//!
+//! The [`FromEnvValue`] and [`TryFromEnvValue`] traits and their implementations only require
+//! the `alloc` feature, so they can be used in environments where the configuration comes
+//! from a build-time or host-provided key/value table via [`parse_from`] and [`try_parse_from`].
+//! Everything that reads from [`std::env`] requires the `std` feature.
+
+use crate::libstd::{BTreeMap, BTreeSet, String, ToOwned, Vec};
+use core::{
     char::ParseCharError,
-    collections::{BTreeMap, BTreeSet, HashSet},
     convert::Infallible,
-    env::{VarError, remove_var},
-    ffi::OsStr,
     fmt::{Debug, Display},
-    hash::{Hash, Hasher},
-    marker::PhantomData,
     num::{
         NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32,
         NonZeroU64, NonZeroU128, NonZeroUsize, ParseFloatError, ParseIntError,
     },
-    rc::Rc,
     str::ParseBoolError,
 };
 
+#[cfg(feature = "std")]
+use std::{
+    collections::HashSet,
+    env::{VarError, remove_var},
+    ffi::OsStr,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    rc::Rc,
+};
+
 /// When reading from the system environment variables, types might want to convert
 /// the value from `getenv` to something useful and this is where this trait comes in.
 pub trait FromEnvValue: Sized {
@@ -64,6 +74,8 @@ pub trait TryFromEnvValue: Sized {
     fn try_from_env_value(value: String) -> Result<Self, Self::Error>;
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 impl<K: TryFromEnvValue + Eq + Hash, V: TryFromEnvValue> TryFromEnvValue for std::collections::HashMap<K, V> {
     type Error = MapTryFromEnvError<K::Error, V::Error>;
 
@@ -113,8 +125,8 @@ impl<K: TryFromEnvValue + Ord, V: TryFromEnvValue> TryFromEnvValue for BTreeMap<
     }
 }
 
-/// Error variant for <code>impl [`TryFromEnvValue`] for [`std::collections::HashMap`]<K, V></code>
-/// and <code>impl [`TryFromEnvValue`] for [`std::collections::BTreeMap`]<K, V></code>.
+/// Error variant for <code>impl [`TryFromEnvValue`] for [`HashMap`](std::collections::HashMap)<K, V></code>
+/// and <code>impl [`TryFromEnvValue`] for [`BTreeMap`]<K, V></code>.
 #[derive(Debug)]
 pub enum MapTryFromEnvError<K, V> {
     Key(K),
@@ -122,7 +134,7 @@ pub enum MapTryFromEnvError<K, V> {
 }
 
 impl<K: Display, V: Display> Display for MapTryFromEnvError<K, V> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Key(s) => Display::fmt(s, f),
             Self::Value(v) => Display::fmt(v, f),
@@ -130,8 +142,8 @@ impl<K: Display, V: Display> Display for MapTryFromEnvError<K, V> {
     }
 }
 
-impl<K: std::error::Error + 'static, V: std::error::Error + 'static> std::error::Error for MapTryFromEnvError<K, V> {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+impl<K: core::error::Error + 'static, V: core::error::Error + 'static> core::error::Error for MapTryFromEnvError<K, V> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
         match self {
             Self::Key(k) => Some(k),
             Self::Value(v) => Some(v),
@@ -139,6 +151,8 @@ impl<K: std::error::Error + 'static, V: std::error::Error + 'static> std::error:
     }
 }
 
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 impl<T: TryFromEnvValue + Eq + Hash> TryFromEnvValue for HashSet<T> {
     type Error = T::Error;
 
@@ -198,15 +212,15 @@ pub struct InvalidLevel(String);
 
 #[cfg(feature = "tracing")]
 #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "tracing")))]
-impl std::fmt::Display for InvalidLevel {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl Display for InvalidLevel {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         write!(f, "invalid log level: '{}'", self.0)
     }
 }
 
 #[cfg(feature = "tracing")]
 #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "tracing")))]
-impl std::error::Error for InvalidLevel {}
+impl core::error::Error for InvalidLevel {}
 
 macro_rules! impl_try_from_env {
     ($($(#[$meta:meta])* $Ty:ty: $Error:ty;)*) => {
@@ -260,6 +274,8 @@ impl_try_from_env!(
     u128: ParseIntError;
     usize: ParseIntError;
 
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
     std::path::PathBuf: Infallible;
 
     #[cfg(feature = "sentry")]
@@ -279,12 +295,63 @@ impl<T: FromEnvValue> TryFromEnvValue for T {
     }
 }
 
+/// Parses the value of `key` from a [`FromEnvValue`] implementation, where the value is
+/// looked up from `lookup` instead of the system environment variables.
+///
+/// Returns `None` if `lookup` didn't find a value for `key`.
+///
+/// ## Example
+/// ```
+/// use azalia_config::env::parse_from;
+///
+/// const VARIABLES: &[(&str, &str)] = &[("HELLO", "world")];
+/// let lookup = |key: &str| VARIABLES.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+///
+/// assert_eq!(parse_from::<_, _, String>(lookup, "HELLO"), Some(String::from("world")));
+/// assert_eq!(parse_from::<_, _, String>(lookup, "WORLD"), None);
+/// ```
+pub fn parse_from<K: AsRef<str>, S: Into<String>, V: FromEnvValue>(
+    lookup: impl FnOnce(&str) -> Option<S>,
+    key: K,
+) -> Option<V> {
+    lookup(key.as_ref()).map(|value| V::from_env_value(value.into()))
+}
+
+/// Parses the value of `key` from a [`TryFromEnvValue`] implementation, where the value is
+/// looked up from `lookup` instead of the system environment variables.
+///
+/// Returns `Ok(None)` if `lookup` didn't find a value for `key`.
+///
+/// ## Example
+/// ```
+/// use azalia_config::env::try_parse_from;
+///
+/// const VARIABLES: &[(&str, &str)] = &[("PORT", "8080"), ("HOST", "localhost")];
+/// let lookup = |key: &str| VARIABLES.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+///
+/// assert_eq!(try_parse_from::<_, _, u16>(lookup, "PORT"), Ok(Some(8080)));
+/// assert!(try_parse_from::<_, _, u16>(lookup, "HOST").is_err());
+/// assert_eq!(try_parse_from::<_, _, u16>(lookup, "WORLD"), Ok(None));
+/// ```
+pub fn try_parse_from<K: AsRef<str>, S: Into<String>, V: TryFromEnvValue>(
+    lookup: impl FnOnce(&str) -> Option<S>,
+    key: K,
+) -> Result<Option<V>, V::Error> {
+    lookup(key.as_ref())
+        .map(|value| V::try_from_env_value(value.into()))
+        .transpose()
+}
+
 /// Parses an environment variable from a [`FromEnvValue`] implementation.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn parse<K: Into<String>, V: FromEnvValue>(key: K) -> Result<V, VarError> {
     std::env::var(key.into()).map(V::from_env_value)
 }
 
 /// Parses an environment variable from a [`TryFromEnvValue`] implementation.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn try_parse<K: Into<String>, V: TryFromEnvValue>(key: K) -> Result<V, TryParseError<V::Error>> {
     match std::env::var(key.into()) {
         Ok(value) => V::try_from_env_value(value).map_err(TryParseError::Parse),
@@ -293,6 +360,8 @@ pub fn try_parse<K: Into<String>, V: TryFromEnvValue>(key: K) -> Result<V, TryPa
 }
 
 /// Analogous to [`try_parse`] but uses a closure to compute the default value.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn try_parse_or<K: Into<String>, V: TryFromEnvValue>(
     key: K,
     default: impl FnOnce() -> V,
@@ -305,6 +374,8 @@ pub fn try_parse_or<K: Into<String>, V: TryFromEnvValue>(
 }
 
 /// Analogous to [`try_parse`] but uses a default value if the environment variable was not found.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn try_parse_or_else<K: Into<String>, V: TryFromEnvValue>(
     key: K,
     default: V,
@@ -319,6 +390,8 @@ pub fn try_parse_or_else<K: Into<String>, V: TryFromEnvValue>(
 /// Anlogous to [`try_parse`] but returns a <code>[`Option`]\<V\></code> instead.
 ///
 /// When the environment variable by the name of `key` doesn't exist, it'll return `None`.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn try_parse_optional<K: Into<String>, V: TryFromEnvValue>(key: K) -> Result<Option<V>, TryParseError<V::Error>> {
     match std::env::var(key.into()) {
         Ok(value) => V::try_from_env_value(value).map(Some).map_err(TryParseError::Parse),
@@ -328,12 +401,15 @@ pub fn try_parse_optional<K: Into<String>, V: TryFromEnvValue>(key: K) -> Result
 }
 
 /// Error variant for [`try_parse`].
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 #[derive(Debug)]
 pub enum TryParseError<V> {
     System(VarError),
     Parse(V),
 }
 
+#[cfg(feature = "std")]
 impl<V: Display> Display for TryParseError<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -343,6 +419,7 @@ impl<V: Display> Display for TryParseError<V> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<V: std::error::Error + 'static> std::error::Error for TryParseError<V> {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -451,11 +528,14 @@ impl<T: FromEnv> TryFromEnv for T {
 ///
 /// This is only meant in testing environments so it is not our issue to deal
 /// with if anything outside of testing goes unsound.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub struct EnvGuard {
     name: String,
     _non_send_and_sync: PhantomData<Rc<()>>,
 }
 
+#[cfg(feature = "std")]
 impl EnvGuard {
     /// Enters the guard and sets the name of the environment variable
     /// to the value of **1**.
@@ -515,20 +595,24 @@ impl EnvGuard {
     }
 }
 
+#[cfg(feature = "std")]
 impl PartialEq for EnvGuard {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
     }
 }
 
+#[cfg(feature = "std")]
 impl Eq for EnvGuard {}
 
+#[cfg(feature = "std")]
 impl Hash for EnvGuard {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.name.hash(state);
     }
 }
 
+#[cfg(feature = "std")]
 impl Drop for EnvGuard {
     fn drop(&mut self) {
         unsafe { remove_var(&self.name) }
@@ -553,11 +637,14 @@ impl Drop for EnvGuard {
 ///
 /// This is only meant in testing environments so it is not our issue to deal
 /// with if anything outside of testing goes unsound.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub struct MultipleEnvGuard {
     _variables: HashSet<EnvGuard>,
     _non_send_sync: PhantomData<Rc<()>>,
 }
 
+#[cfg(feature = "std")]
 impl MultipleEnvGuard {
     /// Enters the guard and sets a iterator of `(key, value)` as [`EnvGuard`]s. On [`Drop`], it'll
     /// call [`remove_var`] of the specified environment variables.
@@ -600,6 +687,8 @@ impl MultipleEnvGuard {
 /// ## Safety
 /// Environment variables are inheritely unsafe to test! See the [`EnvGuard`]'s
 /// Safety documentation about it.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn enter(key: impl Into<String>, f: impl FnOnce()) {
     let _guard = EnvGuard::enter(key);
     f()
@@ -610,6 +699,8 @@ pub fn enter(key: impl Into<String>, f: impl FnOnce()) {
 /// ## Safety
 /// Environment variables are inheritely unsafe to test! See the [`EnvGuard`]'s
 /// Safety documentation about it.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn enter_with(key: impl Into<String>, value: impl AsRef<OsStr>, f: impl FnOnce()) {
     let _guard = EnvGuard::enter_with(key, value);
     f()
@@ -621,12 +712,14 @@ pub fn enter_with(key: impl Into<String>, value: impl AsRef<OsStr>, f: impl FnOn
 /// ## Safety
 /// Environment variables are inheritely unsafe to test! See the [`MultipleEnvGuard`]'s
 /// Safety documentation about it.
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
 pub fn enter_multiple(iter: impl IntoIterator<Item = (impl Into<String>, impl AsRef<OsStr>)>, f: impl FnOnce()) {
     let _guard = MultipleEnvGuard::enter(iter);
     f()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
     use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
@@ -29,18 +29,30 @@
 #[cfg(all(not(feature = "std"), feature = "alloc"))]
 extern crate alloc;
 
-#[cfg(feature = "std")]
-#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+// allows the derive macros to refer to `::azalia_config` within this crate
+#[cfg(feature = "macros")]
+extern crate self as azalia_config;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
 pub mod env;
 pub mod merge;
 
+#[cfg(feature = "schema")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "schema")))]
+pub mod schema;
+
 #[cfg(feature = "std")]
 pub(crate) mod libstd {
     pub use std::{
-        collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+        borrow::{Cow, ToOwned},
+        boxed::Box,
+        collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
         hash::Hash,
         num,
-        string::String,
+        rc::Rc,
+        string::{String, ToString},
+        sync::Arc,
         vec::Vec,
     };
 }
@@ -51,8 +63,12 @@ pub(crate) mod libstd {
 
     #[cfg(feature = "alloc")]
     pub use alloc::{
-        collections::{BTreeMap, BTreeSet},
-        string::String,
+        borrow::{Cow, ToOwned},
+        boxed::Box,
+        collections::{BTreeMap, BTreeSet, VecDeque},
+        rc::Rc,
+        string::{String, ToString},
+        sync::Arc,
         vec::Vec,
     };
 }
@@ -24,33 +24,126 @@
 
 pub mod strategy;
 
-use crate::libstd::{
-    num::{
-        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU16, NonZeroU32, NonZeroU64,
-        NonZeroU8, NonZeroUsize,
-    },
-    String,
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod conflict;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use conflict::*;
+
+mod partial;
+pub use partial::*;
+
+mod three_way;
+pub use three_way::*;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod layers;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use layers::*;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod report;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub use report::*;
+
+use crate::libstd::num::{
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU128, NonZeroUsize,
 };
+use core::time::Duration;
 
 #[cfg(feature = "macros")]
 pub use azalia_config_macros::Merge;
 
+#[cfg(all(feature = "macros", any(feature = "std", feature = "alloc")))]
+pub use azalia_config_macros::TryMerge;
+
+#[cfg(feature = "macros")]
+pub use azalia_config_macros::Merge3;
+
 /// Trait that allows deep merging between the same **type** but possibly different values.
 ///
 /// ***This is also a derive macro when the `macros` feature is avaliable:
 /// <code>#[derive([`Merge`][derive-redirect])]</code>***
 ///
 /// ## Notes
-/// When using the derive macro, the crate path will always be **azalia::config**. You can
-/// set `crate = <path>` when <code>#[derive([`Merge`][derive-redirect])]</code>:
+/// When using the derive macro, the crate path is resolved from your project's `Cargo.toml`:
+/// a direct (or renamed) `azalia-config` dependency is preferred, then the `azalia` crate's
+/// **config** module, and **azalia::config** if neither could be found. You can still set
+/// `crate = <path>` when <code>#[derive([`Merge`][derive-redirect])]</code> if the crate is
+/// re-exported from somewhere else:
 ///
 /// ```ignore
 /// #[merge(crate = some::other::crate)]
 /// ```
 ///
-/// If you're using the standalone crate (`azalia-config`), this is unfortunately required
-/// as the proc-macro doesn't understand the dependency tree of the project and since is mainly
-/// for Noelware's use case, we use the centeralised crate approach.
+/// ### Default-aware merging
+/// Use `#[merge(default)]` on a field, or on the container to apply it to every field, to only
+/// overwrite when the overlay's value isn't `Default::default()` (see [`MergeDefault`]). The
+/// precedence is explicit:
+///
+/// * `#[merge(default)]` or `#[merge(default = "overwrite")]` will use the overlay's value if it was set.
+/// * `#[merge(default = "fill")]` will keep the base's value if it was set.
+///
+/// On the container, it only applies to fields that are primitives, `String`s or `PathBuf`s, or an
+/// `Option` of one; other fields like nested structs, `Vec`s and maps are still merged recursively
+/// with their [`Merge`] implementation, unless `#[merge(default)]` is also used on the field.
+///
+/// ### Reports
+/// The derive macro also implements [`Merge::merge_with_report`], which records the dotted
+/// path of each field that was changed by the overlay into a [`MergeReport`]. Since a strategy
+/// can't be observed, fields that are merged with one (from `#[merge(strategy)]`, `strategy_for`
+/// or the container's strategy) are compared against a copy of themselves and are only recorded
+/// if their type is [`Clone`] + [`PartialEq`]. `#[merge(deep)]` maps are recorded as a whole
+/// when a key was inserted or a value was changed.
+///
+/// ### Partial overlays
+/// `#[merge(partial)]` on a struct will also generate a sparse overlay where every field is
+/// optional and implement [`Partial`] for the struct, see the [`Partial`] trait for more details.
+///
+/// ### Container strategies
+/// `#[merge(strategy = <path>)]` on a struct is the default strategy for every field that
+/// doesn't have its own `#[merge(...)]` attribute, while `#[merge(strategy_for(<type>) = <path>)]`
+/// only applies to fields of that type, which takes precedence over the container's strategy.
+/// A type without generic arguments, like `Vec`, will match any instantiation of it:
+///
+/// ```ignore
+/// #[merge(
+///     strategy = azalia::config::merge::strategy::default::overwrite,
+///     strategy_for(Vec) = azalia::config::merge::strategy::vec::overwrite
+/// )]
+/// ```
+///
+/// ### Optional values
+/// `Option<T>` keeps `self` if it is `Some`, see the [`strategy::option`] module for other strategies.
+/// `#[merge(recurse_options)]` on a struct will use [`strategy::option::recurse`] for every
+/// `Option<T>` field without its own attribute, so nested values that are set in both are merged
+/// instead of being kept. This requires `T: Merge` and is only used by
+/// <code>#[derive([`Merge`][derive-redirect])]</code>.
+///
+/// ### Generics
+/// For generic types, each type parameter that is used by a field that is merged with the trait
+/// itself gets a `T: Merge` bound. This can be overridden with `#[merge(bound = "...")]` when
+/// fields use strategies with other requirements:
+///
+/// ```ignore
+/// #[merge(bound = "T: Default + PartialEq")]
+/// ```
+///
+/// ### Enumerations
+/// The derive macro also supports enumerations: if both values are the same variant, then
+/// the variant's fields are merged like a struct's; otherwise, `self` is replaced with `other`.
+/// This can be overwritten with a container-level strategy, which merges the whole value
+/// instead of each field:
+///
+/// ```ignore
+/// #[merge(strategy = some::strategy::function)]
+/// ```
 ///
 /// ## Example
 /// ```
@@ -59,14 +152,204 @@ pub use azalia_config_macros::Merge;
 ///
 /// #[derive(Merge)]
 /// pub struct Wrapper(u64);
+///
+/// #[derive(Merge)]
+/// pub enum Storage {
+///     Filesystem(String),
+///     S3 { bucket: String, region: Option<String> },
+/// }
 /// # };
 /// ```
 ///
 /// [derive-redirect]: ../merge/derive.Merge.html
 pub trait Merge: Sized {
     fn merge(&mut self, other: Self);
+
+    /// Merges `other` into `self` like [`Merge::merge`] and records the paths of the values
+    /// that were changed into `report`.
+    ///
+    /// The default implementation only calls [`Merge::merge`] and won't record anything, so
+    /// manual implementations should override this.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::{Merge, MergeReport};
+    ///
+    /// let mut report = MergeReport::new();
+    /// let mut port = 8080u16;
+    ///
+    /// port.merge_with_report(3000, &mut report);
+    /// assert_eq!(report.changed(), [""]);
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    fn merge_with_report(&mut self, other: Self, report: &mut MergeReport) {
+        let _ = report;
+        self.merge(other);
+    }
+
+    /// Merges all `layers` in order, where each layer is merged into the value of all the
+    /// layers before it, i.e, defaults, then configuration files, then environment variables.
+    ///
+    /// Returns [`None`] if there were no layers.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::Merge;
+    ///
+    /// let hosts = Vec::merge_all([vec!["a"], vec!["b"], vec!["c"]]);
+    /// assert_eq!(hosts, Some(vec!["a", "b", "c"]));
+    ///
+    /// assert_eq!(Vec::<&str>::merge_all([]), None);
+    /// ```
+    fn merge_all(layers: impl IntoIterator<Item = Self>) -> Option<Self> {
+        let mut layers = layers.into_iter();
+        let mut value = layers.next()?;
+        for layer in layers {
+            value.merge(layer);
+        }
+
+        Some(value)
+    }
+
+    /// Merges a clone of `other` into `self`, for when the layer needs to be kept around.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::Merge;
+    ///
+    /// let overlay = Some(8080u16);
+    /// let mut port = None;
+    ///
+    /// port.merge_from(&overlay);
+    /// assert_eq!(port, overlay);
+    /// ```
+    fn merge_from(&mut self, other: &Self)
+    where
+        Self: Clone,
+    {
+        self.merge(other.clone());
+    }
+}
+
+/// Implements [`Merge::merge_with_report`] only if it is available.
+macro_rules! merge_with_report {
+    (|$self:ident, $other:ident, $report:ident| $body:block) => {
+        #[cfg(any(feature = "std", feature = "alloc"))]
+        fn merge_with_report(&mut $self, $other: Self, $report: &mut MergeReport) $body
+    };
+}
+
+/// Records the current path if a [`Copy`] value was changed by [`Merge::merge`].
+macro_rules! merge_copy_with_report {
+    () => {
+        merge_with_report!(|self, other, report| {
+            let before = *self;
+            self.merge(other);
+            if *self != before {
+                report.record();
+            }
+        });
+    };
+}
+
+/// Used by `#[derive(Merge)]` to only implement [`Merge::merge_with_report`] when it is available.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(any(feature = "std", feature = "alloc"))]
+macro_rules! __merge_with_report {
+    ($($item:tt)*) => { $($item)* };
 }
 
+/// Used by `#[derive(Merge)]` to only implement [`Merge::merge_with_report`] when it is available.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+macro_rules! __merge_with_report {
+    ($($item:tt)*) => {};
+}
+
+#[doc(hidden)]
+pub use __merge_with_report as __with_report;
+
+/// Used by `#[derive(Merge)]` to only implement [`TryMerge`] when it is available.
+#[doc(hidden)]
+pub use __merge_with_report as __with_try_merge;
+
+// declared after `merge_with_report!` so that the value trees can use it
+#[cfg(any(feature = "serde_json", feature = "toml", feature = "serde_yaml_ng"))]
+mod value;
+
+#[cfg(any(feature = "serde_json", feature = "toml", feature = "serde_yaml_ng"))]
+#[cfg_attr(
+    any(noeldoc, docsrs),
+    doc(cfg(any(feature = "serde_json", feature = "toml", feature = "serde_yaml_ng")))
+)]
+pub use value::ArrayPolicy;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "serde")]
+    pub use serde;
+}
+
+/// Default-aware merging for any type that implements [`Default`] and [`PartialEq`], where
+/// a value that is equal to `Self::default()` is considered as unset.
+///
+/// This is implemented for all types that satisfy the bounds and forwards to the
+/// [`strategy::default`] module.
+///
+/// ## Example
+/// ```
+/// use azalia_config::merge::MergeDefault;
+///
+/// // `true` from a base layer isn't overwritten by the default value of a overlay
+/// let mut enabled = true;
+/// enabled.merge_default(false);
+/// assert!(enabled);
+///
+/// // ...but a base layer's value that wasn't set will be filled in
+/// let mut name = String::new();
+/// name.fill_default(String::from("azalia"));
+/// assert_eq!(name, "azalia");
+/// ```
+pub trait MergeDefault: Default + PartialEq {
+    /// Overwrites `self` with `other` unless `other` is `Self::default()`.
+    fn merge_default(&mut self, other: Self) {
+        strategy::default::overwrite(self, other);
+    }
+
+    /// Overwrites `self` with `other` only if `self` is `Self::default()`.
+    fn fill_default(&mut self, other: Self) {
+        strategy::default::fill(self, other);
+    }
+
+    /// [`MergeDefault::merge_default`] that records the current path into `report` if
+    /// `self` was changed.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    fn merge_default_with_report(&mut self, other: Self, report: &mut MergeReport) {
+        if other != Self::default() && *self != other {
+            *self = other;
+            report.record();
+        }
+    }
+
+    /// [`MergeDefault::fill_default`] that records the current path into `report` if
+    /// `self` was changed.
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    fn fill_default_with_report(&mut self, other: Self, report: &mut MergeReport) {
+        let default = Self::default();
+        if *self == default && other != default {
+            *self = other;
+            report.record();
+        }
+    }
+}
+
+impl<T: Default + PartialEq> MergeDefault for T {}
+
 impl Merge for () {
     fn merge(&mut self, _: Self) {}
 }
@@ -77,6 +360,72 @@ impl<T> Merge for Option<T> {
             *self = other.take();
         }
     }
+
+    merge_with_report!(|self, other, report| {
+        if self.is_none() && other.is_some() {
+            report.record();
+        }
+
+        Merge::merge(self, other);
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T: Merge> Merge for crate::libstd::Box<T> {
+    fn merge(&mut self, other: Self) {
+        (**self).merge(*other);
+    }
+
+    merge_with_report!(|self, other, report| {
+        (**self).merge_with_report(*other, report);
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// The inner value is cloned if the [`Rc`](crate::libstd::Rc) is shared, or if `other` is shared.
+impl<T: Merge + Clone> Merge for crate::libstd::Rc<T> {
+    fn merge(&mut self, other: Self) {
+        let other = crate::libstd::Rc::unwrap_or_clone(other);
+        crate::libstd::Rc::make_mut(self).merge(other);
+    }
+
+    merge_with_report!(|self, other, report| {
+        let other = crate::libstd::Rc::unwrap_or_clone(other);
+        crate::libstd::Rc::make_mut(self).merge_with_report(other, report);
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// The inner value is cloned if the [`Arc`](crate::libstd::Arc) is shared, or if `other` is shared.
+impl<T: Merge + Clone> Merge for crate::libstd::Arc<T> {
+    fn merge(&mut self, other: Self) {
+        let other = crate::libstd::Arc::unwrap_or_clone(other);
+        crate::libstd::Arc::make_mut(self).merge(other);
+    }
+
+    merge_with_report!(|self, other, report| {
+        let other = crate::libstd::Arc::unwrap_or_clone(other);
+        crate::libstd::Arc::make_mut(self).merge_with_report(other, report);
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// Borrowed values will be converted into an owned value before merging.
+impl<T: crate::libstd::ToOwned + ?Sized> Merge for crate::libstd::Cow<'_, T>
+where
+    T::Owned: Merge,
+{
+    fn merge(&mut self, other: Self) {
+        self.to_mut().merge(other.into_owned());
+    }
+
+    merge_with_report!(|self, other, report| {
+        self.to_mut().merge_with_report(other.into_owned(), report);
+    });
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -88,6 +437,31 @@ impl<T> Merge for crate::libstd::Vec<T> {
     fn merge(&mut self, other: Self) {
         strategy::vec::extend(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        if !other.is_empty() {
+            report.record();
+        }
+
+        Merge::merge(self, other);
+    });
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// The implementation for this type is very loose and will only extend.
+impl<T> Merge for crate::libstd::VecDeque<T> {
+    fn merge(&mut self, other: Self) {
+        self.extend(other);
+    }
+
+    merge_with_report!(|self, other, report| {
+        if !other.is_empty() {
+            report.record();
+        }
+
+        Merge::merge(self, other);
+    });
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -99,6 +473,14 @@ impl<K: Ord, V> Merge for crate::libstd::BTreeMap<K, V> {
     fn merge(&mut self, other: Self) {
         strategy::maps::btreemap::extend(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        if !other.is_empty() {
+            report.record();
+        }
+
+        Merge::merge(self, other);
+    });
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -110,6 +492,14 @@ impl<T: Ord> Merge for crate::libstd::BTreeSet<T> {
     fn merge(&mut self, other: Self) {
         strategy::sets::btreeset::extend(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        let len = self.len();
+        Merge::merge(self, other);
+        if self.len() != len {
+            report.record();
+        }
+    });
 }
 
 #[cfg(feature = "std")]
@@ -121,6 +511,14 @@ impl<K: crate::libstd::Hash + Eq, V> Merge for crate::libstd::HashMap<K, V> {
     fn merge(&mut self, other: Self) {
         strategy::maps::hashmap::extend(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        if !other.is_empty() {
+            report.record();
+        }
+
+        Merge::merge(self, other);
+    });
 }
 
 #[cfg(feature = "std")]
@@ -132,6 +530,14 @@ impl<T: crate::libstd::Hash + Eq> Merge for crate::libstd::HashSet<T> {
     fn merge(&mut self, other: Self) {
         strategy::sets::hashset::extend(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        let len = self.len();
+        Merge::merge(self, other);
+        if self.len() != len {
+            report.record();
+        }
+    });
 }
 
 /// The implementation for this type is very loose and will only compare
@@ -142,6 +548,14 @@ impl Merge for f32 {
     fn merge(&mut self, other: Self) {
         strategy::f32::without_negative(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        let before = self.to_bits();
+        Merge::merge(self, other);
+        if self.to_bits() != before {
+            report.record();
+        }
+    });
 }
 
 /// The implementation for this type is very loose and will only compare
@@ -152,6 +566,79 @@ impl Merge for f64 {
     fn merge(&mut self, other: Self) {
         strategy::f64::without_negative(self, other);
     }
+
+    merge_with_report!(|self, other, report| {
+        let before = self.to_bits();
+        Merge::merge(self, other);
+        if self.to_bits() != before {
+            report.record();
+        }
+    });
+}
+
+/// Each element is merged with the element at the same position in `other`.
+impl<T: Merge, const N: usize> Merge for [T; N] {
+    fn merge(&mut self, other: Self) {
+        for (lhs, rhs) in self.iter_mut().zip(other) {
+            lhs.merge(rhs);
+        }
+    }
+
+    merge_with_report!(|self, other, report| {
+        for (idx, (lhs, rhs)) in self.iter_mut().zip(other).enumerate() {
+            report.enter(crate::libstd::ToString::to_string(&idx));
+            lhs.merge_with_report(rhs, report);
+            report.leave();
+        }
+    });
+}
+
+macro_rules! impl_tuple {
+    ($(($($T:ident $idx:tt),+))+) => {
+        $(
+            /// Each element is merged with the element at the same position in `other`.
+            impl<$($T: Merge),+> Merge for ($($T,)+) {
+                fn merge(&mut self, other: Self) {
+                    $(self.$idx.merge(other.$idx);)+
+                }
+
+                merge_with_report!(|self, other, report| {
+                    $(
+                        report.enter(stringify!($idx));
+                        self.$idx.merge_with_report(other.$idx, report);
+                        report.leave();
+                    )+
+                });
+            }
+        )+
+    };
+}
+
+impl_tuple! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+/// The implementation for this type is very loose and will treat [`Duration::ZERO`]
+/// as an unset value, like the unsigned integer types.
+impl Merge for Duration {
+    fn merge(&mut self, other: Self) {
+        if !other.is_zero() && *self != other {
+            *self = other;
+        }
+    }
+
+    merge_copy_with_report!();
 }
 
 macro_rules! impl_unsigned_int {
@@ -178,6 +665,8 @@ macro_rules! impl_unsigned_int {
                     *self = other;
                 }
             }
+
+            merge_copy_with_report!();
         })*
     };
 }
@@ -194,6 +683,8 @@ macro_rules! impl_nonzero {
                     *self = unsafe { <$Ty>::new_unchecked(other.get()) };
                 }
             }
+
+            merge_copy_with_report!();
         })*
     };
 }
@@ -210,6 +701,7 @@ impl_nonzero!(
     NonZeroU16
     NonZeroU32
     NonZeroU64
+    NonZeroU128
     NonZeroUsize
 );
 
@@ -228,6 +720,13 @@ macro_rules! impl_partialeq {
                         *self = other;
                     }
                 }
+
+                merge_with_report!(|self, other, report| {
+                    if *self != other {
+                        *self = other;
+                        report.record();
+                    }
+                });
             }
         )*
     };
@@ -246,10 +745,15 @@ impl_partialeq!(
     /// Check out the [`strategy::bool`] module for other strategies.
     bool
 
+    /// The implementation for this type is very loose and will only do comparisons.
+    char
+
     /// The implementation for this type is very loose and will only do comparisons.
     ///
     /// Check out the [`strategy::string`] module for other strategies.
-    String
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    crate::libstd::String
 
     #[cfg(feature = "std")]
     #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
@@ -263,3 +767,71 @@ impl_partialeq!(
     #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "tracing")))]
     tracing::Level
 );
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::Merge;
+    use crate::libstd::{Arc, Box, Cow, Rc, String, VecDeque, num::NonZeroU128};
+    use core::time::Duration;
+
+    #[test]
+    fn smart_pointers() {
+        let mut boxed = Box::new(vec![1]);
+        boxed.merge(Box::new(vec![2]));
+        assert_eq!(*boxed, vec![1, 2]);
+
+        let mut rc = Rc::new(vec![1]);
+        let shared = Rc::clone(&rc);
+        rc.merge(Rc::new(vec![2]));
+
+        assert_eq!(*rc, vec![1, 2]);
+        assert_eq!(*shared, vec![1]);
+
+        let mut arc = Arc::new(Some(1));
+        arc.merge(Arc::new(Some(2)));
+        assert_eq!(*arc, Some(1));
+    }
+
+    #[test]
+    fn cow() {
+        let base = String::from("hello");
+        let mut cow: Cow<'_, str> = Cow::Borrowed(&base);
+
+        cow.merge(Cow::Owned(String::from("world")));
+        assert_eq!(cow, "world");
+        assert_eq!(base, "hello");
+    }
+
+    #[test]
+    fn tuples_and_arrays() {
+        let mut tuple = (Some(1), vec![1], 0u8);
+        tuple.merge((Some(2), vec![2], 4));
+        assert_eq!(tuple, (Some(1), vec![1, 2], 4));
+
+        let mut array = [None, Some(2)];
+        array.merge([Some(1), Some(3)]);
+        assert_eq!(array, [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn other_types() {
+        let mut deque = VecDeque::from([1]);
+        deque.merge(VecDeque::from([2]));
+        assert_eq!(deque, [1, 2]);
+
+        let mut duration = Duration::from_secs(5);
+        duration.merge(Duration::ZERO);
+        assert_eq!(duration, Duration::from_secs(5));
+
+        duration.merge(Duration::from_secs(10));
+        assert_eq!(duration, Duration::from_secs(10));
+
+        let mut ch = 'a';
+        ch.merge('b');
+        assert_eq!(ch, 'b');
+
+        let mut nonzero = NonZeroU128::new(1).unwrap();
+        nonzero.merge(NonZeroU128::new(2).unwrap());
+        assert_eq!(nonzero.get(), 2);
+    }
+}
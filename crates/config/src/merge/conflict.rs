@@ -0,0 +1,401 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use super::Merge;
+use crate::libstd::{
+    Arc, BTreeMap, BTreeSet, Box, Cow, Rc, String, ToOwned, ToString, Vec, VecDeque,
+    num::{
+        NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32,
+        NonZeroU64, NonZeroU128, NonZeroUsize,
+    },
+};
+use core::{
+    fmt::{self, Display},
+    time::Duration,
+};
+
+#[cfg(feature = "std")]
+use crate::libstd::Hash;
+
+/// Analogous to [`Merge`] but merging can fail if the two values conflict with
+/// each other.
+///
+/// ***This is also a derive macro when the `macros` feature is avaliable:
+/// <code>#[derive([`TryMerge`][derive-redirect])]</code>***
+///
+/// This is implemented for the types that implement [`Merge`] in this crate and for every
+/// <code>#[derive([`Merge`](super::Merge))]</code> type, which never fail, so that they can
+/// be used as fields of a <code>#[derive([`TryMerge`][derive-redirect])]</code> type. A type
+/// that implements [`Merge`] by hand needs to implement [`TryMerge`] as well to be used as one.
+///
+/// Containers like `Option<T>`, `Vec<T>` and maps merge the same way as their [`Merge`]
+/// implementation and are [`TryMerge`] for any `T`, while `Box<T>`, `Rc<T>`, `Arc<T>`, arrays
+/// and tuples merge their values with [`TryMerge`], so a conflict in them is returned.
+///
+/// ## Limitations
+/// A type can only derive either <code>#[derive([`Merge`](super::Merge))]</code> or
+/// <code>#[derive([`TryMerge`][derive-redirect])]</code>, and a [`TryMerge`] type is never [`Merge`]. It
+/// can't be used where a [`Merge`] type is expected, like [`Merge::merge_all`] or the strategies that
+/// merge the values of a container; use [`TryMerge::try_merge_all`] or [`Layers::try_effective`] to
+/// merge its layers instead.
+///
+/// [`TryMerge::try_merge`] stops at the first field that conflicted, so fields that were merged
+/// before it keep their merged values and `self` is left partially merged. Use
+/// [`TryMerge::try_merge_or_rollback`] to keep `self` unchanged if merging failed.
+///
+/// ## Notes
+/// The derive macro supports all of the attributes that <code>#[derive([`Merge`](super::Merge))]</code>
+/// does with the following additions for fields:
+///
+/// * `#[merge(conflict = "error")]` will fail if both values were set (not `Default::default()`) and
+///   are different, see [`strategy::conflict::error`](super::strategy::conflict::error).
+/// * `#[merge(try_strategy = <path>)]` uses a fallible strategy with the signature of
+///   `fn(&mut T, T) -> Result<(), MergeConflict>`.
+///
+/// Each field that fails will prepend its name to the [`MergeConflict`]'s path, so a conflict
+/// in `url` from a field named `database` will be reported as `database.url`.
+///
+/// ## Example
+/// ```
+/// # const _: &str = stringify! {
+/// use azalia_config::merge::TryMerge;
+///
+/// #[derive(TryMerge)]
+/// pub struct Database {
+///     #[merge(conflict = "error")]
+///     pub url: String,
+///     pub replicas: Vec<String>,
+/// }
+/// # };
+/// ```
+///
+/// [derive-redirect]: ../merge/derive.TryMerge.html
+/// [`Layers::try_effective`]: super::Layers::try_effective
+pub trait TryMerge: Sized {
+    /// Merges `other` into `self`, or returns a [`MergeConflict`] if both values
+    /// couldn't be merged. `self` is left partially merged if this fails.
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict>;
+
+    /// Merges `other` into a clone of `self`, which replaces `self` only if merging
+    /// didn't fail.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::TryMerge;
+    ///
+    /// let mut hosts = vec![String::from("a")];
+    /// assert!(hosts.try_merge_or_rollback(vec![String::from("b")]).is_ok());
+    /// assert_eq!(hosts, ["a", "b"]);
+    /// ```
+    fn try_merge_or_rollback(&mut self, other: Self) -> Result<(), MergeConflict>
+    where
+        Self: Clone,
+    {
+        let mut merged = self.clone();
+        merged.try_merge(other)?;
+
+        *self = merged;
+        Ok(())
+    }
+
+    /// Merges all `layers` in order into the first layer, analogous to [`Merge::merge_all`].
+    ///
+    /// Returns `Ok(None)` if there were no layers, or the first [`MergeConflict`].
+    fn try_merge_all(layers: impl IntoIterator<Item = Self>) -> Result<Option<Self>, MergeConflict> {
+        let mut layers = layers.into_iter();
+        let Some(mut value) = layers.next() else {
+            return Ok(None);
+        };
+
+        for layer in layers {
+            value.try_merge(layer)?;
+        }
+
+        Ok(Some(value))
+    }
+}
+
+/// Implements [`TryMerge`] with the type's [`Merge`] implementation, which never fails.
+macro_rules! impl_merge {
+    ($(
+        $(#[$meta:meta])*
+        [$($generics:tt)*] $Ty:ty;
+    )*) => {
+        $(
+            $(#[$meta])*
+            impl<$($generics)*> TryMerge for $Ty {
+                fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+                    Merge::merge(self, other);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_merge! {
+    [] ();
+    [] bool;
+    [] char;
+    [] i8;
+    [] i16;
+    [] i32;
+    [] i64;
+    [] i128;
+    [] isize;
+    [] u8;
+    [] u16;
+    [] u32;
+    [] u64;
+    [] u128;
+    [] usize;
+    [] f32;
+    [] f64;
+    [] Duration;
+    [] NonZeroI8;
+    [] NonZeroI16;
+    [] NonZeroI32;
+    [] NonZeroI64;
+    [] NonZeroI128;
+    [] NonZeroIsize;
+    [] NonZeroU8;
+    [] NonZeroU16;
+    [] NonZeroU32;
+    [] NonZeroU64;
+    [] NonZeroU128;
+    [] NonZeroUsize;
+    [] String;
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+    [] std::path::PathBuf;
+
+    #[cfg(feature = "url")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "url")))]
+    [] url::Url;
+
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_json")))]
+    [] serde_json::Value;
+
+    #[cfg(feature = "toml")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "toml")))]
+    [] toml::Value;
+
+    #[cfg(feature = "serde_yaml_ng")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_yaml_ng")))]
+    [] serde_yaml_ng::Value;
+
+    /// Merges the same way as `Option`'s [`Merge`] implementation, so the values are never merged.
+    [T] Option<T>;
+
+    /// Merges the same way as `Vec`'s [`Merge`] implementation, so the elements are never merged.
+    [T] Vec<T>;
+    [T] VecDeque<T>;
+
+    /// Merges the same way as `BTreeMap`'s [`Merge`] implementation, so the values are never merged.
+    [K: Ord, V] BTreeMap<K, V>;
+    [T: Ord] BTreeSet<T>;
+
+    /// Merges the same way as `HashMap`'s [`Merge`] implementation, so the values are never merged.
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+    [K: Hash + Eq, V] std::collections::HashMap<K, V>;
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+    [T: Hash + Eq] std::collections::HashSet<T>;
+}
+
+impl<T: TryMerge> TryMerge for Box<T> {
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+        (**self).try_merge(*other)
+    }
+}
+
+/// The inner value is cloned if the [`Rc`] is shared, or if `other` is shared.
+impl<T: TryMerge + Clone> TryMerge for Rc<T> {
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+        Rc::make_mut(self).try_merge(Rc::unwrap_or_clone(other))
+    }
+}
+
+/// The inner value is cloned if the [`Arc`] is shared, or if `other` is shared.
+impl<T: TryMerge + Clone> TryMerge for Arc<T> {
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+        Arc::make_mut(self).try_merge(Arc::unwrap_or_clone(other))
+    }
+}
+
+/// Borrowed values will be converted into an owned value before merging.
+impl<T: ToOwned + ?Sized> TryMerge for Cow<'_, T>
+where
+    T::Owned: TryMerge,
+{
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+        self.to_mut().try_merge(other.into_owned())
+    }
+}
+
+/// Each element is merged with the element at the same position in `other`, a conflict
+/// contains the index of the element that conflicted.
+impl<T: TryMerge, const N: usize> TryMerge for [T; N] {
+    fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+        for (idx, (lhs, rhs)) in self.iter_mut().zip(other).enumerate() {
+            lhs.try_merge(rhs)
+                .map_err(|conflict| conflict.within(idx.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($($T:ident $idx:tt),+))+) => {
+        $(
+            /// Each element is merged with the element at the same position in `other`, a
+            /// conflict contains the index of the element that conflicted.
+            impl<$($T: TryMerge),+> TryMerge for ($($T,)+) {
+                fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+                    $(
+                        self.$idx
+                            .try_merge(other.$idx)
+                            .map_err(|conflict| conflict.within(stringify!($idx)))?;
+                    )+
+
+                    Ok(())
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+/// Error type for [`TryMerge`] that contains the path to the value that conflicted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeConflict {
+    path: Vec<Cow<'static, str>>,
+    reason: Option<Cow<'static, str>>,
+}
+
+impl MergeConflict {
+    /// Creates a new [`MergeConflict`] for the current value.
+    pub fn new() -> MergeConflict {
+        MergeConflict::default()
+    }
+
+    /// Sets a human-readable reason of why both values conflicted.
+    pub fn with_reason<R: Into<Cow<'static, str>>>(mut self, reason: R) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Prepends `segment` to the path of this conflict. This is used when a field of
+    /// a struct failed to merge.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::MergeConflict;
+    ///
+    /// let conflict = MergeConflict::new().within("url").within("database");
+    /// assert_eq!(conflict.path(), "database.url");
+    /// ```
+    pub fn within<S: Into<Cow<'static, str>>>(mut self, segment: S) -> Self {
+        self.path.insert(0, segment.into());
+        self
+    }
+
+    /// Returns the segments of the path that conflicted, from the outermost value.
+    pub fn segments(&self) -> &[Cow<'static, str>] {
+        &self.path
+    }
+
+    /// Returns the dotted path of the value that conflicted, this will be empty if the
+    /// conflict was from the value that was merged.
+    pub fn path(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// Returns the reason of why both values conflicted, if any.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("conflicting values")?;
+        if !self.path.is_empty() {
+            write!(f, " for `{}`", self.path())?;
+        }
+
+        if let Some(reason) = &self.reason {
+            write!(f, ": {reason}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::error::Error for MergeConflict {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(MergeConflict::new().to_string(), "conflicting values");
+        assert_eq!(
+            MergeConflict::new().within("url").within("database").to_string(),
+            "conflicting values for `database.url`"
+        );
+
+        assert_eq!(
+            MergeConflict::new()
+                .with_reason("both layers set a value")
+                .within("port")
+                .to_string(),
+            "conflicting values for `port`: both layers set a value"
+        );
+    }
+
+    #[test]
+    fn merge_types_never_fail() {
+        let mut value = vec![1];
+        assert!(value.try_merge(vec![2]).is_ok());
+        assert_eq!(value, [1, 2]);
+    }
+}
@@ -0,0 +1,172 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use super::{Merge, MergeConflict, TryMerge};
+use crate::libstd::Vec;
+use core::slice;
+
+/// An ordered stack of configuration layers, where later layers are merged into earlier ones.
+///
+/// Unlike [`Merge::merge_all`], the individual layers are kept around so that the effective
+/// value can be recomputed with [`Layers::effective`] when any one of them changes, like when
+/// a configuration file is reloaded.
+///
+/// ## Example
+/// ```
+/// use azalia_config::merge::Layers;
+///
+/// let mut layers = Layers::new();
+/// let defaults = layers.push(vec!["localhost"]);
+/// let file = layers.push(vec!["db.internal"]);
+///
+/// assert_eq!(layers.effective(), Some(vec!["localhost", "db.internal"]));
+///
+/// // the configuration file was reloaded
+/// layers.replace(file, vec!["db.example.com"]);
+/// assert_eq!(layers.effective(), Some(vec!["localhost", "db.example.com"]));
+/// # let _ = defaults;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layers<T> {
+    layers: Vec<T>,
+}
+
+impl<T> Default for Layers<T> {
+    fn default() -> Self {
+        Layers { layers: Vec::new() }
+    }
+}
+
+impl<T> Layers<T> {
+    /// Creates a new, empty [`Layers`].
+    pub fn new() -> Layers<T> {
+        Layers::default()
+    }
+
+    /// Pushes `layer` on top of all the other layers and returns its index.
+    pub fn push(&mut self, layer: T) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    /// Replaces the layer at `index` with `layer` and returns the previous layer,
+    /// or [`None`] if there is no layer at `index`.
+    pub fn replace(&mut self, index: usize, layer: T) -> Option<T> {
+        self.layers
+            .get_mut(index)
+            .map(|existing| core::mem::replace(existing, layer))
+    }
+
+    /// Returns the layer at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.layers.get(index)
+    }
+
+    /// Returns a mutable reference to the layer at `index`.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.layers.get_mut(index)
+    }
+
+    /// Returns the amount of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Returns `true` if there are no layers.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    /// Returns an iterator over all layers, from the lowest to the highest priority.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.layers.iter()
+    }
+
+    /// Returns all the layers, from the lowest to the highest priority.
+    pub fn into_inner(self) -> Vec<T> {
+        self.layers
+    }
+}
+
+impl<T: Merge + Clone> Layers<T> {
+    /// Merges a clone of every layer in order, or returns [`None`] if there are no layers.
+    pub fn effective(&self) -> Option<T> {
+        T::merge_all(self.layers.iter().cloned())
+    }
+}
+
+impl<T: TryMerge + Clone> Layers<T> {
+    /// Merges a clone of every layer in order with [`TryMerge`], or returns [`None`] if there are
+    /// no layers. The first [`MergeConflict`] is returned if any of the layers conflicted.
+    pub fn try_effective(&self) -> Result<Option<T>, MergeConflict> {
+        T::try_merge_all(self.layers.iter().cloned())
+    }
+}
+
+impl<T> From<Vec<T>> for Layers<T> {
+    fn from(layers: Vec<T>) -> Self {
+        Layers { layers }
+    }
+}
+
+impl<T> FromIterator<T> for Layers<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Layers {
+            layers: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for Layers<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.layers.extend(iter);
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Layers<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective() {
+        let mut layers = [Some(8080u16), None, Some(3000)].into_iter().collect::<Layers<_>>();
+
+        assert_eq!(layers.effective(), Some(Some(8080)));
+
+        layers.replace(0, None);
+        assert_eq!(layers.effective(), Some(Some(3000)));
+
+        *layers.get_mut(1).unwrap() = Some(443);
+        assert_eq!(layers.effective(), Some(Some(443)));
+
+        assert_eq!(layers.replace(3, None), None);
+        assert_eq!(Layers::<Option<u16>>::new().effective(), None);
+    }
+}
@@ -0,0 +1,84 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use super::Merge;
+
+/// Types that have a sparse overlay, where every field is optional, that can be applied
+/// on top of a complete value.
+///
+/// ***This is generated by <code>#[derive([`Merge`][derive-redirect])]</code> with
+/// `#[merge(partial)]` on a struct when the `macros` feature is avaliable.***
+///
+/// The generated type is named `Partial<Name>` (which can be changed with
+/// `#[merge(partial(name = ...))]`) and has the same fields as the original struct, except
+/// skipped fields, where each field is wrapped in an [`Option`] unless it already was
+/// one. Fields with `#[merge(partial)]` will use their type's [`Partial::Partial`] instead
+/// of an [`Option`] to recurse into nested structs.
+///
+/// Partial layers implement [`Merge`], where values that were set in the overlay will
+/// always take precedence, so they can be composed before they are applied.
+///
+/// ## Notes
+/// The generated type only derives [`Default`]; other traits can be derived with
+/// `#[merge(partial(derive(Debug, Clone)))]`. When the `serde` feature is enabled, it will also
+/// implement `serde::Deserialize` and keep the `rename_all`, `deny_unknown_fields`, `rename`
+/// and `alias` serde attributes from the original struct.
+///
+/// ## Example
+/// ```
+/// # const _: &str = stringify! {
+/// use azalia_config::merge::{Merge, Partial};
+///
+/// #[derive(Merge)]
+/// #[merge(partial)]
+/// pub struct Config {
+///     pub name: String,
+///
+///     #[merge(partial)]
+///     pub server: Server,
+/// }
+///
+/// #[derive(Merge)]
+/// #[merge(partial)]
+/// pub struct Server {
+///     pub host: String,
+///     pub port: u16,
+/// }
+///
+/// let mut overlay = PartialConfig::default();
+/// overlay.merge(PartialConfig { name: Some(String::from("azalia")), ..Default::default() });
+/// overlay.merge(PartialConfig {
+///     server: PartialServer { port: Some(3000), ..Default::default() },
+///     ..Default::default()
+/// });
+///
+/// config.apply(overlay);
+/// # };
+/// ```
+///
+/// [derive-redirect]: ../merge/derive.Merge.html
+pub trait Partial: Sized {
+    /// The sparse overlay of this type.
+    type Partial: Default + Merge;
+
+    /// Applies every value that was set in `partial` onto `self`.
+    fn apply(&mut self, partial: Self::Partial);
+}
@@ -0,0 +1,161 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::libstd::{Cow, String, Vec};
+use core::{
+    fmt::{self, Display},
+    slice,
+};
+
+/// Records the dotted paths of every value that was changed by
+/// [`Merge::merge_with_report`](super::Merge::merge_with_report).
+///
+/// The report keeps track of the path that is currently being merged, implementations
+/// of [`Merge`](super::Merge) will [`enter`](MergeReport::enter) a segment before
+/// merging a field and [`leave`](MergeReport::leave) it afterwards, and leaf values
+/// will [`record`](MergeReport::record) the current path if the overlay changed them.
+///
+/// The same report can be re-used for multiple layers, a path is only recorded once.
+///
+/// ## Example
+/// ```
+/// use azalia_config::merge::{Merge, MergeReport};
+///
+/// let mut report = MergeReport::new();
+/// let mut value = (Some(8080u16), String::from("localhost"));
+///
+/// value.merge_with_report((None, String::from("0.0.0.0")), &mut report);
+/// assert_eq!(report.changed(), ["1"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MergeReport {
+    scope: Vec<Cow<'static, str>>,
+    changed: Vec<String>,
+}
+
+impl MergeReport {
+    /// Creates a new, empty [`MergeReport`].
+    pub fn new() -> MergeReport {
+        MergeReport::default()
+    }
+
+    /// Pushes `segment` onto the path that is currently being merged.
+    pub fn enter<S: Into<Cow<'static, str>>>(&mut self, segment: S) {
+        self.scope.push(segment.into());
+    }
+
+    /// Pops the last segment that was [entered](MergeReport::enter).
+    pub fn leave(&mut self) {
+        self.scope.pop();
+    }
+
+    /// Records that the value at the current path was changed.
+    pub fn record(&mut self) {
+        let path = self.scope.join(".");
+        if !self.changed.contains(&path) {
+            self.changed.push(path);
+        }
+    }
+
+    /// Returns the dotted paths of all the changed values, in the order they were changed.
+    pub fn changed(&self) -> &[String] {
+        &self.changed
+    }
+
+    /// Returns `true` if `path` was changed.
+    pub fn contains(&self, path: &str) -> bool {
+        self.changed.iter().any(|p| p == path)
+    }
+
+    /// Returns `true` if no values were changed.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+
+    /// Returns an iterator over the dotted paths of all the changed values.
+    pub fn iter(&self) -> slice::Iter<'_, String> {
+        self.changed.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a MergeReport {
+    type Item = &'a String;
+    type IntoIter = slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Formats the changed paths as a comma-separated list, i.e, `server.port, logging.level`.
+impl Display for MergeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, path) in self.changed.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+
+            f.write_str(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::merge::Merge;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn scopes() {
+        let mut report = MergeReport::new();
+        report.enter("server");
+        report.enter("port");
+        report.record();
+        report.record();
+        report.leave();
+        report.leave();
+
+        report.enter("logging");
+        report.enter("level");
+        report.record();
+
+        assert_eq!(report.changed(), ["server.port", "logging.level"]);
+        assert_eq!(report.to_string(), "server.port, logging.level");
+        assert!(report.contains("server.port"));
+        assert!(!report.contains("server"));
+    }
+
+    #[test]
+    fn builtin_types() {
+        let mut report = MergeReport::new();
+        let mut value = (0u32, Some(1), [false, false], vec![1], BTreeSet::from([1]));
+
+        value.merge_with_report((0, Some(2), [false, true], vec![], BTreeSet::from([1])), &mut report);
+        assert!(!report.is_empty());
+        assert_eq!(report.changed(), ["2.1"]);
+
+        value.merge_with_report((5, None, [true, true], vec![2], BTreeSet::from([2])), &mut report);
+        assert_eq!(report.changed(), ["2.1", "0", "2.0", "3", "4"]);
+    }
+}
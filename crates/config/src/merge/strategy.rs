@@ -21,6 +21,7 @@
 
 //! A module for defining common ways to merge types.
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 #[deprecated(
     since = "0.1.0",
     note = "used in old versions of azalia before crates.io release. scheduled for removal in v0.2"
@@ -31,6 +32,8 @@ pub use string as strings;
 ///
 /// String slices are not supported in this module as string slices are considered immutable
 /// and shouldn't be tampered with.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
 pub mod string {
     use crate::libstd::String;
 
@@ -85,6 +88,128 @@ pub mod string {
     }
 }
 
+/// Default-aware strategies for any type that implements [`Default`] and [`PartialEq`], where
+/// a value that is equal to `T::default()` is considered as unset.
+///
+/// Unlike the built-in implementations of [`Merge`](crate::merge::Merge) for primitives, this treats
+/// every type the same: an overlay's `false`, `0`, `0.0` or empty string will never clobber
+/// a value set in a previous layer.
+///
+/// These are also available as methods on the [`MergeDefault`](crate::merge::MergeDefault) trait and with
+/// <code>#[merge(default)]</code> when using <code>#[derive([`Merge`](crate::merge::Merge))]</code>.
+pub mod default {
+    /// Overwrites `lhs` with `rhs` only if `rhs` is not `T::default()`, so `rhs` takes
+    /// precedence when it was set.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::default::overwrite;
+    ///
+    /// let mut enabled = true;
+    ///
+    /// overwrite(&mut enabled, false);
+    /// assert!(enabled);
+    ///
+    /// let mut ratio = 0.5f64;
+    ///
+    /// overwrite(&mut ratio, -1.0);
+    /// assert_eq!(ratio, -1.0);
+    /// ```
+    pub fn overwrite<T: Default + PartialEq>(lhs: &mut T, rhs: T) {
+        if rhs != T::default() {
+            *lhs = rhs;
+        }
+    }
+
+    /// Overwrites `lhs` with `rhs` only if `lhs` is `T::default()`, so `lhs` takes
+    /// precedence when it was set.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::default::fill;
+    ///
+    /// let mut port = 0u16;
+    ///
+    /// fill(&mut port, 8080);
+    /// assert_eq!(port, 8080);
+    ///
+    /// fill(&mut port, 3000);
+    /// assert_eq!(port, 8080);
+    /// ```
+    pub fn fill<T: Default + PartialEq>(lhs: &mut T, rhs: T) {
+        if *lhs == T::default() {
+            *lhs = rhs;
+        }
+    }
+}
+
+/// Fallible strategies that reject an overlay, these are used by
+/// <code>#[derive([`TryMerge`](crate::merge::TryMerge))]</code>.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub mod conflict {
+    use crate::merge::MergeConflict;
+
+    /// Fails if both `lhs` and `rhs` were set (not `T::default()`) and are different. Otherwise,
+    /// `rhs` is used if it was set.
+    ///
+    /// This is used by <code>#[merge(conflict = "error")]</code>.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::conflict::error;
+    ///
+    /// let mut url = String::new();
+    /// assert!(error(&mut url, String::from("postgres://a")).is_ok());
+    /// assert!(error(&mut url, String::new()).is_ok());
+    /// assert!(error(&mut url, String::from("postgres://a")).is_ok());
+    /// assert!(error(&mut url, String::from("postgres://b")).is_err());
+    /// assert_eq!(url, "postgres://a");
+    /// ```
+    pub fn error<T: Default + PartialEq>(lhs: &mut T, rhs: T) -> Result<(), MergeConflict> {
+        let default = T::default();
+        if rhs == default {
+            return Ok(());
+        }
+
+        if *lhs != default && *lhs != rhs {
+            return Err(MergeConflict::new());
+        }
+
+        *lhs = rhs;
+        Ok(())
+    }
+}
+
+/// Strategies for [`Merge3`](crate::merge::Merge3), where `lhs` is *ours*.
+pub mod three_way {
+    /// Replaces `ours` with `theirs` only if `theirs` was changed relative to `base`, so a
+    /// change from *theirs* always takes precedence.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::three_way::theirs_if_changed;
+    ///
+    /// let mut port = 3000u16;
+    ///
+    /// theirs_if_changed(&mut port, 8080, 8080);
+    /// assert_eq!(port, 3000);
+    ///
+    /// theirs_if_changed(&mut port, 8080, 4000);
+    /// assert_eq!(port, 4000);
+    /// ```
+    pub fn theirs_if_changed<T: PartialEq>(ours: &mut T, base: T, theirs: T) {
+        if theirs != base {
+            *ours = theirs;
+        }
+    }
+
+    /// Keeps `ours` and ignores any changes from `theirs`.
+    pub fn ours<T>(ours: &mut T, base: T, theirs: T) {
+        let _ = (ours, base, theirs);
+    }
+}
+
 /// Other strategies for merging boolean values. The default strategy will compare if `lhs != rhs`.
 pub mod bool {
     /// Merge `lhs <- rhs` if `lhs` == `false`.
@@ -108,6 +233,314 @@ pub mod bool {
     }
 }
 
+/// Other strategies for merging [`Option`]s. The default strategy is [`keep`], which only
+/// uses `rhs` if `lhs` is [`None`].
+pub mod option {
+    use crate::merge::Merge;
+
+    /// Uses `rhs` if `lhs` is [`None`], so a value that was already set is never replaced.
+    /// This is the same as `Option`'s [`Merge`] implementation.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::option::keep;
+    ///
+    /// let mut level = None;
+    ///
+    /// keep(&mut level, Some("info"));
+    /// assert_eq!(level, Some("info"));
+    ///
+    /// keep(&mut level, Some("debug"));
+    /// assert_eq!(level, Some("info"));
+    /// ```
+    pub fn keep<T>(lhs: &mut Option<T>, rhs: Option<T>) {
+        if lhs.is_none() {
+            *lhs = rhs;
+        }
+    }
+
+    /// Uses `rhs` if it is [`Some`], so an overlay can replace a value that was already set.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::option::overwrite_some;
+    ///
+    /// let mut level = Some("info");
+    ///
+    /// overwrite_some(&mut level, Some("debug"));
+    /// assert_eq!(level, Some("debug"));
+    ///
+    /// overwrite_some(&mut level, None);
+    /// assert_eq!(level, Some("debug"));
+    /// ```
+    pub fn overwrite_some<T>(lhs: &mut Option<T>, rhs: Option<T>) {
+        if rhs.is_some() {
+            *lhs = rhs;
+        }
+    }
+
+    /// Merges both values with [`Merge::merge`] if both are [`Some`], otherwise `rhs` is only
+    /// used if `lhs` is [`None`].
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::option::recurse;
+    ///
+    /// let mut hosts = Some(vec!["a"]);
+    ///
+    /// recurse(&mut hosts, Some(vec!["b"]));
+    /// assert_eq!(hosts, Some(vec!["a", "b"]));
+    ///
+    /// recurse(&mut hosts, None);
+    /// assert_eq!(hosts, Some(vec!["a", "b"]));
+    /// ```
+    pub fn recurse<T: Merge>(lhs: &mut Option<T>, rhs: Option<T>) {
+        match (lhs.as_mut(), rhs) {
+            (Some(lhs), Some(rhs)) => lhs.merge(rhs),
+            (None, rhs) => *lhs = rhs,
+            (Some(_), None) => {}
+        }
+    }
+
+    /// [`recurse`] for [`TryMerge`](crate::merge::TryMerge) types, which returns the
+    /// [`MergeConflict`](crate::merge::MergeConflict) if both values are [`Some`] and conflicted.
+    ///
+    /// This is used by `#[merge(recurse_options)]` when using
+    /// <code>#[derive([`TryMerge`](crate::merge::TryMerge))]</code>.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::{strategy::{conflict, option::try_recurse}, MergeConflict};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Url(String);
+    ///
+    /// impl azalia_config::merge::TryMerge for Url {
+    ///     fn try_merge(&mut self, other: Self) -> Result<(), MergeConflict> {
+    ///         conflict::error(&mut self.0, other.0)
+    ///     }
+    /// }
+    ///
+    /// let mut url = None;
+    ///
+    /// assert!(try_recurse(&mut url, Some(Url(String::from("postgres://a")))).is_ok());
+    /// assert!(try_recurse(&mut url, None).is_ok());
+    /// assert!(try_recurse(&mut url, Some(Url(String::from("postgres://b")))).is_err());
+    /// assert_eq!(url, Some(Url(String::from("postgres://a"))));
+    /// ```
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    pub fn try_recurse<T: crate::merge::TryMerge>(
+        lhs: &mut Option<T>,
+        rhs: Option<T>,
+    ) -> Result<(), crate::merge::MergeConflict> {
+        match (lhs.as_mut(), rhs) {
+            (Some(lhs), Some(rhs)) => lhs.try_merge(rhs),
+            (None, rhs) => {
+                *lhs = rhs;
+                Ok(())
+            }
+
+            (Some(_), None) => Ok(()),
+        }
+    }
+
+    /// Used by `#[merge(recurse_options)]` to implement [`Merge::merge_with_report`].
+    #[doc(hidden)]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    pub fn __recurse_with_report<T: Merge>(
+        lhs: &mut Option<T>,
+        rhs: Option<T>,
+        report: &mut crate::merge::MergeReport,
+    ) {
+        match (lhs.as_mut(), rhs) {
+            (Some(lhs), Some(rhs)) => lhs.merge_with_report(rhs, report),
+            (_, rhs) => lhs.merge_with_report(rhs, report),
+        }
+    }
+}
+
+/// Generic strategies for all integer, [`NonZero`](core::num::NonZero) and floating point types.
+///
+/// These are useful for limits where the most permissive ([`max`]) or most restrictive
+/// ([`min`]) value should be used across all layers.
+pub mod numeric {
+    use crate::libstd::num::{
+        NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32,
+        NonZeroU64, NonZeroU128, NonZeroUsize,
+    };
+
+    /// Numeric types that can be used with the strategies in this module. This trait is sealed
+    /// and is implemented for all integer, [`NonZero`](core::num::NonZero) and floating point types.
+    pub trait Numeric: Copy + PartialOrd + private::Sealed {
+        /// Returns the sum of both values, which saturates at the bounds of the type instead
+        /// of overflowing.
+        ///
+        /// For signed [`NonZero`](core::num::NonZero) types, `self` is returned if the sum
+        /// would be zero.
+        fn saturating_add(self, other: Self) -> Self;
+    }
+
+    /// Keeps the largest value of `lhs` and `rhs`.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::numeric::max;
+    ///
+    /// let mut max_connections = 100u32;
+    ///
+    /// max(&mut max_connections, 50);
+    /// assert_eq!(max_connections, 100);
+    ///
+    /// max(&mut max_connections, 200);
+    /// assert_eq!(max_connections, 200);
+    /// ```
+    pub fn max<T: Numeric>(lhs: &mut T, rhs: T) {
+        if rhs > *lhs {
+            *lhs = rhs;
+        }
+    }
+
+    /// Keeps the smallest value of `lhs` and `rhs`.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::numeric::min;
+    ///
+    /// let mut timeout = 30.0f64;
+    ///
+    /// min(&mut timeout, 60.0);
+    /// assert_eq!(timeout, 30.0);
+    ///
+    /// min(&mut timeout, 5.0);
+    /// assert_eq!(timeout, 5.0);
+    /// ```
+    pub fn min<T: Numeric>(lhs: &mut T, rhs: T) {
+        if rhs < *lhs {
+            *lhs = rhs;
+        }
+    }
+
+    /// Adds `rhs` to `lhs`, which saturates at the bounds of the type instead of overflowing.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::numeric::saturating_add;
+    ///
+    /// let mut retries = 250u8;
+    ///
+    /// saturating_add(&mut retries, 3);
+    /// assert_eq!(retries, 253);
+    ///
+    /// saturating_add(&mut retries, 10);
+    /// assert_eq!(retries, u8::MAX);
+    /// ```
+    pub fn saturating_add<T: Numeric>(lhs: &mut T, rhs: T) {
+        *lhs = lhs.saturating_add(rhs);
+    }
+
+    /// Returns a strategy that overwrites `lhs` with `rhs` clamped between `lo` and `hi`. `lhs` is
+    /// left unchanged if `rhs` is `NaN`.
+    ///
+    /// ## Panics
+    /// Panics if `lo` is greater than `hi`, or if either of them is `NaN`.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::numeric::clamp;
+    ///
+    /// let mut workers = 4usize;
+    ///
+    /// clamp(1, 16)(&mut workers, 64);
+    /// assert_eq!(workers, 16);
+    ///
+    /// clamp(1, 16)(&mut workers, 8);
+    /// assert_eq!(workers, 8);
+    /// ```
+    ///
+    /// This can also be used with <code>#[merge(strategy)]</code>:
+    ///
+    /// ```ignore
+    /// #[merge(strategy = strategy::numeric::clamp(1, 16))]
+    /// pub workers: usize,
+    /// ```
+    pub fn clamp<T: Numeric>(lo: T, hi: T) -> impl Fn(&mut T, T) {
+        assert!(lo <= hi, "`lo` must be less than or equal to `hi`");
+
+        move |lhs, rhs| {
+            // `NaN` isn't comparable to anything, including itself
+            if rhs.partial_cmp(&rhs).is_none() {
+                return;
+            }
+
+            *lhs = if rhs < lo {
+                lo
+            } else if rhs > hi {
+                hi
+            } else {
+                rhs
+            };
+        }
+    }
+
+    macro_rules! impl_numeric {
+        (
+            integers: [$($Int:ty)*];
+            unsigned: [$($Unsigned:ty)*];
+            signed: [$($Signed:ty)*];
+            floats: [$($Float:ty)*];
+        ) => {
+            $(
+                impl private::Sealed for $Int {}
+                impl Numeric for $Int {
+                    fn saturating_add(self, other: Self) -> Self {
+                        <$Int>::saturating_add(self, other)
+                    }
+                }
+            )*
+
+            $(
+                impl private::Sealed for $Unsigned {}
+                impl Numeric for $Unsigned {
+                    fn saturating_add(self, other: Self) -> Self {
+                        <$Unsigned>::saturating_add(self, other.get())
+                    }
+                }
+            )*
+
+            $(
+                impl private::Sealed for $Signed {}
+                impl Numeric for $Signed {
+                    fn saturating_add(self, other: Self) -> Self {
+                        <$Signed>::new(self.get().saturating_add(other.get())).unwrap_or(self)
+                    }
+                }
+            )*
+
+            $(
+                impl private::Sealed for $Float {}
+                impl Numeric for $Float {
+                    fn saturating_add(self, other: Self) -> Self {
+                        // floating points saturate at the infinities
+                        self + other
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_numeric! {
+        integers: [u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize];
+        unsigned: [NonZeroU8 NonZeroU16 NonZeroU32 NonZeroU64 NonZeroU128 NonZeroUsize];
+        signed: [NonZeroI8 NonZeroI16 NonZeroI32 NonZeroI64 NonZeroI128 NonZeroIsize];
+        floats: [f32 f64];
+    }
+
+    mod private {
+        pub trait Sealed {}
+    }
+}
+
 macro_rules! mk_floating_strategies {
     ($Ty:ty) => {
         #[doc = concat!("Merges any [`", stringify!($Ty), "`][prim@", stringify!($Ty), "] without allowing negatives")]
@@ -178,6 +611,7 @@ pub mod f64 {
     mk_floating_strategies!(f64);
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
 macro_rules! mk_collection_strategies {
     (
         $Ty:ty => ($($f:tt)*)
@@ -201,14 +635,139 @@ macro_rules! mk_collection_strategies {
 #[cfg(any(feature = "std", feature = "alloc"))]
 #[cfg_attr(any(noeldoc, docsrs), cfg(any(feature = "std", feature = "alloc")))]
 pub mod maps {
+    /// Trait for maps that can be merged with [`deep`]. This is implemented for both
+    /// [`BTreeMap`](crate::libstd::BTreeMap) and [`HashMap`](crate::libstd::HashMap).
+    pub trait DeepMerge {
+        /// Merges `other` into `self` where the values of colliding keys are merged with
+        /// [`Merge::merge`](crate::merge::Merge::merge) instead of being replaced.
+        fn deep_merge(&mut self, other: Self);
+
+        /// Same as [`deep_merge`](DeepMerge::deep_merge), but records the current path into
+        /// `report` if a key was inserted or the value of a colliding key was changed.
+        ///
+        /// The default implementation doesn't record anything, the implementations for
+        /// [`BTreeMap`](crate::libstd::BTreeMap) and [`HashMap`](crate::libstd::HashMap) override it.
+        fn deep_merge_with_report(&mut self, other: Self, report: &mut crate::merge::MergeReport)
+        where
+            Self: Sized,
+        {
+            let _ = report;
+            self.deep_merge(other);
+        }
+    }
+
+    /// Merges all of `rhs` into `lhs`: colliding keys will have their values merged
+    /// with [`Merge::merge`](crate::merge::Merge::merge) and new keys will be inserted.
+    ///
+    /// This is used by <code>#[merge(deep)]</code> when using <code>#[derive([`Merge`](crate::merge::Merge))]</code>.
+    pub fn deep<M: DeepMerge>(lhs: &mut M, rhs: M) {
+        lhs.deep_merge(rhs);
+    }
+
+    /// Used by `#[merge(deep)]` to implement [`Merge::merge_with_report`](crate::merge::Merge::merge_with_report).
+    #[doc(hidden)]
+    pub fn __deep_with_report<M: DeepMerge>(lhs: &mut M, rhs: M, report: &mut crate::merge::MergeReport) {
+        lhs.deep_merge_with_report(rhs, report);
+    }
+
     /// Other strategies for merging [`BTreeMap`](crate::libstd::BTreeMap)s. The default strategy will extend the
     /// collection.
     #[cfg(any(feature = "std", feature = "alloc"))]
     #[cfg_attr(any(noeldoc, docsrs), cfg(any(feature = "std", feature = "alloc")))]
     pub mod btreemap {
-        use crate::libstd::BTreeMap;
+        use crate::{
+            libstd::BTreeMap,
+            merge::{Merge, Merge3},
+        };
 
         mk_collection_strategies!(BTreeMap<K, V> => (K: Ord, V));
+
+        /// Merges all of `rhs` into `lhs`: colliding keys will have their values merged
+        /// with [`Merge::merge`] and new keys will be inserted.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::maps::btreemap::deep;
+        /// use std::collections::BTreeMap;
+        ///
+        /// let mut a = BTreeMap::from([("primary", vec![1]), ("secondary", vec![2])]);
+        /// let b = BTreeMap::from([("primary", vec![3]), ("tertiary", vec![4])]);
+        ///
+        /// deep(&mut a, b);
+        /// assert_eq!(a, BTreeMap::from([("primary", vec![1, 3]), ("secondary", vec![2]), ("tertiary", vec![4])]));
+        /// ```
+        pub fn deep<K: Ord, V: Merge>(lhs: &mut BTreeMap<K, V>, rhs: BTreeMap<K, V>) {
+            for (key, value) in rhs {
+                match lhs.get_mut(&key) {
+                    Some(existing) => existing.merge(value),
+                    None => {
+                        lhs.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        /// Three-way merge for maps, where each key is resolved on its own:
+        ///
+        /// * keys that *theirs* removed are removed from `ours`,
+        /// * keys that *theirs* added are inserted into `ours`, replacing any value that `ours` added,
+        /// * keys that are in all maps have their values merged with [`Merge3::merge3`], and
+        /// * keys that `ours` removed are only inserted back if *theirs* changed them.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::maps::btreemap::three_way;
+        /// use std::collections::BTreeMap;
+        ///
+        /// let base = BTreeMap::from([("a", 1), ("b", 2)]);
+        /// let mut ours = BTreeMap::from([("a", 1), ("b", 20)]);
+        ///
+        /// three_way(&mut ours, base, BTreeMap::from([("a", 10), ("b", 2), ("c", 3)]));
+        /// assert_eq!(ours, BTreeMap::from([("a", 10), ("b", 20), ("c", 3)]));
+        /// ```
+        pub fn three_way<K: Ord, V: Merge3 + PartialEq>(
+            ours: &mut BTreeMap<K, V>,
+            mut base: BTreeMap<K, V>,
+            theirs: BTreeMap<K, V>,
+        ) {
+            for key in base.keys().filter(|key| !theirs.contains_key(*key)) {
+                ours.remove(key);
+            }
+
+            for (key, value) in theirs {
+                match (base.remove(&key), ours.get_mut(&key)) {
+                    (Some(base), Some(existing)) => existing.merge3(base, value),
+                    (Some(base), None) if base == value => {}
+                    _ => {
+                        ours.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        impl<K: Ord, V: Merge> super::DeepMerge for BTreeMap<K, V> {
+            fn deep_merge(&mut self, other: Self) {
+                deep(self, other);
+            }
+
+            fn deep_merge_with_report(&mut self, other: Self, report: &mut crate::merge::MergeReport) {
+                // keys aren't path segments, so changes within the map are recorded on the map itself
+                let mut changed = crate::merge::MergeReport::new();
+                for (key, value) in other {
+                    match self.get_mut(&key) {
+                        Some(existing) => existing.merge_with_report(value, &mut changed),
+                        None => {
+                            self.insert(key, value);
+                            changed.record();
+                        }
+                    }
+                }
+
+                if !changed.is_empty() {
+                    report.record();
+                }
+            }
+        }
     }
 
     /// Other strategies for merging [`HashMap`](crate::libstd::HashMap)s. The default strategy will extend the
@@ -216,13 +775,89 @@ pub mod maps {
     #[cfg(feature = "std")]
     #[cfg_attr(any(noeldoc, docsrs), cfg(feature = "std"))]
     pub mod hashmap {
-        use crate::libstd::{Hash, HashMap};
+        use crate::{
+            libstd::{Hash, HashMap},
+            merge::{Merge, Merge3},
+        };
 
         mk_collection_strategies!(HashMap<K, V> => (K: Hash + Eq, V));
+
+        /// Merges all of `rhs` into `lhs`: colliding keys will have their values merged
+        /// with [`Merge::merge`] and new keys will be inserted.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::maps::hashmap::deep;
+        /// use std::collections::HashMap;
+        ///
+        /// let mut a = HashMap::from([("primary", vec![1]), ("secondary", vec![2])]);
+        /// let b = HashMap::from([("primary", vec![3]), ("tertiary", vec![4])]);
+        ///
+        /// deep(&mut a, b);
+        /// assert_eq!(a, HashMap::from([("primary", vec![1, 3]), ("secondary", vec![2]), ("tertiary", vec![4])]));
+        /// ```
+        pub fn deep<K: Hash + Eq, V: Merge>(lhs: &mut HashMap<K, V>, rhs: HashMap<K, V>) {
+            for (key, value) in rhs {
+                match lhs.get_mut(&key) {
+                    Some(existing) => existing.merge(value),
+                    None => {
+                        lhs.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        /// Three-way merge for maps, where each key is resolved on its own. Check out
+        /// [`btreemap::three_way`](super::btreemap::three_way) for more details.
+        pub fn three_way<K: Hash + Eq, V: Merge3 + PartialEq>(
+            ours: &mut HashMap<K, V>,
+            mut base: HashMap<K, V>,
+            theirs: HashMap<K, V>,
+        ) {
+            for key in base.keys().filter(|key| !theirs.contains_key(*key)) {
+                ours.remove(key);
+            }
+
+            for (key, value) in theirs {
+                match (base.remove(&key), ours.get_mut(&key)) {
+                    (Some(base), Some(existing)) => existing.merge3(base, value),
+                    (Some(base), None) if base == value => {}
+                    _ => {
+                        ours.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        impl<K: Hash + Eq, V: Merge> super::DeepMerge for HashMap<K, V> {
+            fn deep_merge(&mut self, other: Self) {
+                deep(self, other);
+            }
+
+            fn deep_merge_with_report(&mut self, other: Self, report: &mut crate::merge::MergeReport) {
+                // keys aren't path segments, so changes within the map are recorded on the map itself
+                let mut changed = crate::merge::MergeReport::new();
+                for (key, value) in other {
+                    match self.get_mut(&key) {
+                        Some(existing) => existing.merge_with_report(value, &mut changed),
+                        None => {
+                            self.insert(key, value);
+                            changed.record();
+                        }
+                    }
+                }
+
+                if !changed.is_empty() {
+                    report.record();
+                }
+            }
+        }
     }
 }
 
 /// Collection of strategies related to [`HashSet`](crate::libstd::HashSet) and [`BTreeSet`](crate::libstd::BTreeSet).
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
 pub mod sets {
     /// Other strategies for merging [`BTreeSet`](crate::libstd::BTreeSet)s. The default strategy will extend the
     /// collection.
@@ -243,21 +878,120 @@ pub mod sets {
                 lhs.append(&mut right);
             }
         }
+
+        /// Only keeps the elements of `lhs` that are also in `rhs`.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::sets::btreeset::intersection;
+        /// use std::collections::BTreeSet;
+        ///
+        /// let mut features = BTreeSet::from(["a", "b", "c"]);
+        /// intersection(&mut features, BTreeSet::from(["b", "c", "d"]));
+        ///
+        /// assert_eq!(features, BTreeSet::from(["b", "c"]));
+        /// ```
+        pub fn intersection<T: Ord>(lhs: &mut BTreeSet<T>, rhs: BTreeSet<T>) {
+            lhs.retain(|value| rhs.contains(value));
+        }
+
+        /// Removes all the elements of `rhs` from `lhs`.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::sets::btreeset::difference;
+        /// use std::collections::BTreeSet;
+        ///
+        /// let mut features = BTreeSet::from(["a", "b", "c"]);
+        /// difference(&mut features, BTreeSet::from(["b", "d"]));
+        ///
+        /// assert_eq!(features, BTreeSet::from(["a", "c"]));
+        /// ```
+        pub fn difference<T: Ord>(lhs: &mut BTreeSet<T>, rhs: BTreeSet<T>) {
+            lhs.retain(|value| !rhs.contains(value));
+        }
+
+        /// Three-way merge for sets: elements that *theirs* removed from `base` are removed
+        /// from `ours`, and elements that *theirs* added are inserted into `ours`.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::sets::btreeset::three_way;
+        /// use std::collections::BTreeSet;
+        ///
+        /// let mut ours = BTreeSet::from([1, 2, 4]);
+        /// three_way(&mut ours, BTreeSet::from([1, 2]), BTreeSet::from([2, 3]));
+        ///
+        /// assert_eq!(ours, BTreeSet::from([2, 3, 4]));
+        /// ```
+        pub fn three_way<T: Ord>(ours: &mut BTreeSet<T>, base: BTreeSet<T>, theirs: BTreeSet<T>) {
+            for removed in base.difference(&theirs) {
+                ours.remove(removed);
+            }
+
+            ours.extend(theirs.into_iter().filter(|value| !base.contains(value)));
+        }
     }
 
     /// Other strategies for merging [`HashSet`](crate::libstd::HashSet)s. The default strategy will extend the
     /// collection.
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
     pub mod hashset {
         use crate::libstd::{Hash, HashSet};
 
         mk_collection_strategies!(HashSet<T> => (T: Hash + Eq));
+
+        /// Only keeps the elements of `lhs` that are also in `rhs`.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::sets::hashset::intersection;
+        /// use std::collections::HashSet;
+        ///
+        /// let mut features = HashSet::from(["a", "b", "c"]);
+        /// intersection(&mut features, HashSet::from(["b", "c", "d"]));
+        ///
+        /// assert_eq!(features, HashSet::from(["b", "c"]));
+        /// ```
+        pub fn intersection<T: Hash + Eq>(lhs: &mut HashSet<T>, rhs: HashSet<T>) {
+            lhs.retain(|value| rhs.contains(value));
+        }
+
+        /// Removes all the elements of `rhs` from `lhs`.
+        ///
+        /// ## Example
+        /// ```
+        /// use azalia_config::merge::strategy::sets::hashset::difference;
+        /// use std::collections::HashSet;
+        ///
+        /// let mut features = HashSet::from(["a", "b", "c"]);
+        /// difference(&mut features, HashSet::from(["b", "d"]));
+        ///
+        /// assert_eq!(features, HashSet::from(["a", "c"]));
+        /// ```
+        pub fn difference<T: Hash + Eq>(lhs: &mut HashSet<T>, rhs: HashSet<T>) {
+            lhs.retain(|value| !rhs.contains(value));
+        }
+
+        /// Three-way merge for sets: elements that *theirs* removed from `base` are removed
+        /// from `ours`, and elements that *theirs* added are inserted into `ours`.
+        pub fn three_way<T: Hash + Eq>(ours: &mut HashSet<T>, base: HashSet<T>, theirs: HashSet<T>) {
+            for removed in base.difference(&theirs) {
+                ours.remove(removed);
+            }
+
+            ours.extend(theirs.into_iter().filter(|value| !base.contains(value)));
+        }
     }
 }
 
-/// Other strategies for merging [`Vec`]s. The default strategy will extend the
+/// Other strategies for merging [`Vec`](crate::libstd::Vec)s. The default strategy will extend the
 /// collection.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
 pub mod vec {
-    use crate::libstd::Vec;
+    use crate::{libstd::Vec, merge::Merge};
 
     mk_collection_strategies!(Vec<T> => (T));
 
@@ -273,4 +1007,210 @@ pub mod vec {
             lhs.append(&mut right);
         }
     }
+
+    /// Inserts all the elements from `rhs` before the elements of `lhs`.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::vec::prepend;
+    ///
+    /// let mut paths = vec!["/usr/lib"];
+    /// prepend(&mut paths, vec!["/opt/lib"]);
+    ///
+    /// assert_eq!(paths, ["/opt/lib", "/usr/lib"]);
+    /// ```
+    pub fn prepend<T>(lhs: &mut Vec<T>, rhs: Vec<T>) {
+        lhs.splice(0..0, rhs);
+    }
+
+    /// Extends all of `rhs` into `lhs` and removes every duplicated element afterwards, only
+    /// the first occurrence of an element is kept.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::vec::dedup;
+    ///
+    /// let mut hosts = vec!["a", "b", "a"];
+    /// dedup(&mut hosts, vec!["c", "b"]);
+    ///
+    /// assert_eq!(hosts, ["a", "b", "c"]);
+    /// ```
+    pub fn dedup<T: PartialEq>(lhs: &mut Vec<T>, rhs: Vec<T>) {
+        lhs.extend(rhs);
+
+        let mut idx = 0;
+        while idx < lhs.len() {
+            if lhs[..idx].contains(&lhs[idx]) {
+                lhs.remove(idx);
+            } else {
+                idx += 1;
+            }
+        }
+    }
+
+    /// Appends the elements from `rhs` that aren't in `lhs` yet. Unlike [`dedup`], duplicated
+    /// elements that were already in `lhs` are kept.
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::vec::union;
+    ///
+    /// let mut plugins = vec!["auth", "auth"];
+    /// union(&mut plugins, vec!["metrics", "auth", "metrics"]);
+    ///
+    /// assert_eq!(plugins, ["auth", "auth", "metrics"]);
+    /// ```
+    pub fn union<T: PartialEq>(lhs: &mut Vec<T>, rhs: Vec<T>) {
+        for value in rhs {
+            if !lhs.contains(&value) {
+                lhs.push(value);
+            }
+        }
+    }
+
+    /// Returns a strategy that merges the elements that share the same key with
+    /// [`Merge::merge`], elements from `rhs` with a new key are appended.
+    ///
+    /// When used with <code>#[merge(strategy)]</code>, the closure's parameter needs a
+    /// type annotation:
+    ///
+    /// ```ignore
+    /// #[merge(strategy = strategy::vec::merge_by_key(|upstream: &Upstream| upstream.name.clone()))]
+    /// pub upstreams: Vec<Upstream>,
+    /// ```
+    ///
+    /// ## Example
+    /// ```
+    /// use azalia_config::merge::strategy::vec::merge_by_key;
+    ///
+    /// let mut upstreams = vec![(1u32, Some(8080u16)), (2, None)];
+    /// merge_by_key(|upstream: &(u32, Option<u16>)| upstream.0)(&mut upstreams, vec![(2, Some(3000)), (3, None)]);
+    ///
+    /// assert_eq!(upstreams, [(1, Some(8080)), (2, Some(3000)), (3, None)]);
+    /// ```
+    pub fn merge_by_key<T: Merge, K: PartialEq, F: Fn(&T) -> K>(key: F) -> impl Fn(&mut Vec<T>, Vec<T>) {
+        move |lhs, rhs| {
+            for value in rhs {
+                let k = key(&value);
+                match lhs.iter_mut().find(|existing| key(existing) == k) {
+                    Some(existing) => existing.merge(value),
+                    None => lhs.push(value),
+                }
+            }
+        }
+    }
+}
+
+macro_rules! mk_value_strategies {
+    ($(
+        $(#[$meta:meta])*
+        $module:ident($Value:ty, $merge:ident) => $example:literal;
+    )*) => {
+        $(
+            $(#[$meta])*
+            pub mod $module {
+                use crate::merge::{ArrayPolicy, value};
+
+                /// Merges `rhs` into `lhs` with the given [`ArrayPolicy`] for arrays.
+                ///
+                /// ## Example
+                /// ```
+                #[doc = $example]
+                /// ```
+                pub fn with(policy: ArrayPolicy) -> impl Fn(&mut $Value, $Value) {
+                    move |lhs, rhs| value::$merge(lhs, rhs, policy)
+                }
+
+                /// Merges `rhs` into `lhs`, where arrays from `rhs` replace the arrays in `lhs`.
+                pub fn replace(lhs: &mut $Value, rhs: $Value) {
+                    value::$merge(lhs, rhs, ArrayPolicy::Replace);
+                }
+
+                /// Merges `rhs` into `lhs`, where the elements of arrays from `rhs` are appended.
+                pub fn append(lhs: &mut $Value, rhs: $Value) {
+                    value::$merge(lhs, rhs, ArrayPolicy::Append);
+                }
+
+                /// Merges `rhs` into `lhs`, where elements of arrays at the same index are merged.
+                pub fn merge_by_index(lhs: &mut $Value, rhs: $Value) {
+                    value::$merge(lhs, rhs, ArrayPolicy::MergeByIndex);
+                }
+            }
+        )*
+    };
+}
+
+mk_value_strategies! {
+    /// Strategies for merging [`serde_json::Value`]s.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_json")))]
+    json(serde_json::Value, json) => "use azalia_config::merge::{ArrayPolicy, strategy::json};
+use serde_json::json;
+
+let mut plugins = json!({ \"auth\": { \"providers\": [\"github\"] } });
+json::with(ArrayPolicy::Append)(&mut plugins, json!({ \"auth\": { \"providers\": [\"gitlab\"] } }));
+
+assert_eq!(plugins, json!({ \"auth\": { \"providers\": [\"github\", \"gitlab\"] } }));";
+
+    /// Strategies for merging [`toml::Value`]s.
+    #[cfg(feature = "toml")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "toml")))]
+    toml(toml::Value, toml) => "use azalia_config::merge::{ArrayPolicy, strategy};
+use toml::Value;
+
+let mut ports = Value::Array(vec![80.into()]);
+strategy::toml::with(ArrayPolicy::Append)(&mut ports, Value::Array(vec![443.into()]));
+
+assert_eq!(ports, Value::Array(vec![80.into(), 443.into()]));";
+
+    /// Strategies for merging [`serde_yaml_ng::Value`]s.
+    #[cfg(feature = "serde_yaml_ng")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_yaml_ng")))]
+    yaml(serde_yaml_ng::Value, yaml) => "use azalia_config::merge::{ArrayPolicy, strategy::yaml};
+use serde_yaml_ng::Value;
+
+let mut ports = Value::Sequence(vec![80.into()]);
+yaml::with(ArrayPolicy::Append)(&mut ports, Value::Sequence(vec![443.into()]));
+
+assert_eq!(ports, Value::Sequence(vec![80.into(), 443.into()]));";
+}
+
+/// Used by `#[merge(strategy)]` to implement [`Merge::merge_with_report`](crate::merge::Merge::merge_with_report):
+/// the field is snapshotted before the strategy runs if it is [`Clone`] + [`PartialEq`], while
+/// any other type resolves to [`Opaque`](__report::Opaque) through autoref and isn't recorded.
+#[doc(hidden)]
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod __report {
+    use core::marker::PhantomData;
+
+    pub struct Probe<T>(pub PhantomData<fn() -> T>);
+
+    pub trait Comparable<T> {
+        fn snapshot(&self, value: &T) -> Option<T>;
+        fn changed(&self, before: Option<T>, after: &T) -> bool;
+    }
+
+    impl<T: Clone + PartialEq> Comparable<T> for Probe<T> {
+        fn snapshot(&self, value: &T) -> Option<T> {
+            Some(value.clone())
+        }
+
+        fn changed(&self, before: Option<T>, after: &T) -> bool {
+            before.is_some_and(|before| before != *after)
+        }
+    }
+
+    pub trait Opaque<T> {
+        fn snapshot(&self, value: &T) -> Option<T> {
+            let _ = value;
+            None
+        }
+
+        fn changed(&self, before: Option<T>, after: &T) -> bool {
+            let _ = (before, after);
+            false
+        }
+    }
+
+    impl<T> Opaque<T> for &Probe<T> {}
 }
@@ -0,0 +1,277 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use super::strategy;
+use crate::libstd::num::{
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8, NonZeroU16, NonZeroU32,
+    NonZeroU64, NonZeroU128, NonZeroUsize,
+};
+use core::time::Duration;
+
+/// Three-way merging between a common `base` and two values that were derived from it,
+/// where `self` is *ours* and `theirs` is the other value.
+///
+/// ***This is also a derive macro when the `macros` feature is avaliable:
+/// <code>#[derive([`Merge3`][derive-redirect])]</code>***
+///
+/// This is useful for configuration reloads: the configuration that was loaded at startup is
+/// the `base`, values that were changed at runtime are *ours*, and the reloaded configuration is
+/// *theirs*. Each value will only take *theirs* if it was changed relative to `base`, so values
+/// that were only changed at runtime are kept.
+///
+/// Values without any structure, like numbers and strings, are resolved with
+/// [`strategy::three_way::theirs_if_changed`]. Sets are merged element-wise and maps are
+/// merged key-wise, see the `three_way` strategies for [`BTreeSet`](strategy::sets::btreeset::three_way)
+/// and [`BTreeMap`](strategy::maps::btreemap::three_way).
+///
+/// ## Notes
+/// The derive macro shares the same attributes as <code>#[derive([`Merge`](super::Merge))]</code>, though
+/// only `#[merge(skip)]` and `#[merge(three_way = <path>)]` are used, where the strategy has the
+/// signature of `fn(&mut T, T, T)` as `(ours, base, theirs)`.
+///
+/// For enumerations, if *theirs* is a different variant than `base`, then `self` is replaced.
+/// Otherwise, the fields of the variant are merged if `self` is the same variant.
+///
+/// ## Example
+/// ```
+/// use azalia_config::merge::Merge3;
+///
+/// let base = (String::from("azalia"), 8080u16);
+///
+/// let mut ours = (String::from("noelware"), 8080u16);
+/// ours.merge3(base, (String::from("azalia"), 3000));
+///
+/// assert_eq!(ours, (String::from("noelware"), 3000));
+/// ```
+///
+/// [derive-redirect]: ../merge/derive.Merge3.html
+pub trait Merge3: Sized {
+    /// Merges `theirs` into `self`, where both values were derived from `base`.
+    fn merge3(&mut self, base: Self, theirs: Self);
+}
+
+impl<T: Merge3 + PartialEq> Merge3 for Option<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        match (self.as_mut(), base, theirs) {
+            (Some(ours), Some(base), Some(theirs)) => ours.merge3(base, theirs),
+            (_, base, theirs) => strategy::three_way::theirs_if_changed(self, base, theirs),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+impl<T: Merge3> Merge3 for crate::libstd::Box<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        (**self).merge3(*base, *theirs);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// The elements are compared as a whole since their positions can't be tracked.
+impl<T: PartialEq> Merge3 for crate::libstd::Vec<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::three_way::theirs_if_changed(self, base, theirs);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// The elements are compared as a whole since their positions can't be tracked.
+impl<T: PartialEq> Merge3 for crate::libstd::VecDeque<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::three_way::theirs_if_changed(self, base, theirs);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// Check out [`strategy::sets::btreeset::three_way`] on how this is merged.
+impl<T: Ord> Merge3 for crate::libstd::BTreeSet<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::sets::btreeset::three_way(self, base, theirs);
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+/// Check out [`strategy::maps::btreemap::three_way`] on how this is merged.
+impl<K: Ord, V: Merge3 + PartialEq> Merge3 for crate::libstd::BTreeMap<K, V> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::maps::btreemap::three_way(self, base, theirs);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+/// Check out [`strategy::sets::hashset::three_way`] on how this is merged.
+impl<T: crate::libstd::Hash + Eq> Merge3 for crate::libstd::HashSet<T> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::sets::hashset::three_way(self, base, theirs);
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+/// Check out [`strategy::maps::hashmap::three_way`] on how this is merged.
+impl<K: crate::libstd::Hash + Eq, V: Merge3 + PartialEq> Merge3 for crate::libstd::HashMap<K, V> {
+    fn merge3(&mut self, base: Self, theirs: Self) {
+        strategy::maps::hashmap::three_way(self, base, theirs);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($($T:ident $idx:tt),+))+) => {
+        $(
+            /// Each element is merged with the elements at the same position.
+            impl<$($T: Merge3),+> Merge3 for ($($T,)+) {
+                fn merge3(&mut self, base: Self, theirs: Self) {
+                    $(self.$idx.merge3(base.$idx, theirs.$idx);)+
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple! {
+    (A 0)
+    (A 0, B 1)
+    (A 0, B 1, C 2)
+    (A 0, B 1, C 2, D 3)
+    (A 0, B 1, C 2, D 3, E 4)
+    (A 0, B 1, C 2, D 3, E 4, F 5)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+    (A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+macro_rules! impl_theirs_if_changed {
+    (
+        $(
+            $(#[$meta:meta])*
+            $Ty:ty
+        )*
+    ) => {
+        $(
+            $(#[$meta])*
+            impl Merge3 for $Ty {
+                fn merge3(&mut self, base: Self, theirs: Self) {
+                    strategy::three_way::theirs_if_changed(self, base, theirs);
+                }
+            }
+        )*
+    };
+}
+
+impl_theirs_if_changed!(
+    ()
+    bool
+    char
+    f32
+    f64
+    Duration
+
+    u8
+    u16
+    u32
+    u64
+    u128
+    usize
+
+    i8
+    i16
+    i32
+    i64
+    i128
+    isize
+
+    NonZeroI8
+    NonZeroI16
+    NonZeroI32
+    NonZeroI64
+    NonZeroI128
+    NonZeroIsize
+
+    NonZeroU8
+    NonZeroU16
+    NonZeroU32
+    NonZeroU64
+    NonZeroU128
+    NonZeroUsize
+
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(any(feature = "std", feature = "alloc"))))]
+    crate::libstd::String
+
+    #[cfg(feature = "std")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+    std::path::PathBuf
+
+    #[cfg(feature = "url")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "url")))]
+    url::Url
+
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "tracing")))]
+    tracing::Level
+);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashSet};
+
+    #[test]
+    fn options() {
+        let mut ours = Some(1);
+        ours.merge3(Some(1), None);
+        assert_eq!(ours, None);
+
+        let mut ours = Some((1, 2));
+        ours.merge3(Some((1, 1)), Some((3, 1)));
+        assert_eq!(ours, Some((3, 2)));
+    }
+
+    #[test]
+    fn sets() {
+        let mut ours = HashSet::from([1, 2, 4]);
+        ours.merge3(HashSet::from([1, 2]), HashSet::from([2, 3]));
+        assert_eq!(ours, HashSet::from([2, 3, 4]));
+    }
+
+    #[test]
+    fn maps() {
+        let base = BTreeMap::from([("a", 1), ("b", 2), ("c", 3), ("d", 4)]);
+
+        // `b` was changed at runtime and `d` was removed at runtime
+        let mut ours = BTreeMap::from([("a", 1), ("b", 20), ("c", 3)]);
+
+        // `a` was changed in the file, `c` was removed, `d` was changed and `e` was added
+        let theirs = BTreeMap::from([("a", 10), ("b", 2), ("d", 40), ("e", 5)]);
+
+        ours.merge3(base, theirs);
+        assert_eq!(ours, BTreeMap::from([("a", 10), ("b", 20), ("d", 40), ("e", 5)]));
+    }
+}
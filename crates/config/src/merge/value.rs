@@ -0,0 +1,232 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! [`Merge`] implementations for the value trees of `serde_json`, `toml` and `serde_yaml_ng`,
+//! which are useful for configuration sections that don't have a fixed structure.
+
+use super::{Merge, MergeReport};
+use crate::libstd::Vec;
+
+/// Policy for merging two arrays within a value tree.
+///
+/// Objects (or tables and mappings) are always merged key-wise, where the values of
+/// colliding keys are merged recursively. Any other value is replaced, except for a
+/// `null` value from `other`, which is treated as unset like [`None`].
+///
+/// The [`Merge`] implementations use [`ArrayPolicy::Replace`], the other policies are
+/// available as strategies in the `json`, `toml` and `yaml` modules of [`strategy`](super::strategy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayPolicy {
+    /// The array from `other` replaces the array in `self`.
+    #[default]
+    Replace,
+
+    /// The elements from `other` are appended to the array in `self`.
+    Append,
+
+    /// Elements at the same index are merged recursively, and the remaining elements
+    /// from `other` are appended.
+    MergeByIndex,
+}
+
+impl ArrayPolicy {
+    fn merge<T>(self, lhs: &mut Vec<T>, rhs: Vec<T>, merge: impl Fn(&mut T, T)) {
+        match self {
+            ArrayPolicy::Replace => *lhs = rhs,
+            ArrayPolicy::Append => lhs.extend(rhs),
+            ArrayPolicy::MergeByIndex => {
+                let mut rhs = rhs.into_iter();
+                for (l, r) in lhs.iter_mut().zip(rhs.by_ref()) {
+                    merge(l, r);
+                }
+
+                lhs.extend(rhs);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+pub(crate) fn json(lhs: &mut serde_json::Value, rhs: serde_json::Value, policy: ArrayPolicy) {
+    use serde_json::Value;
+
+    match (lhs, rhs) {
+        (_, Value::Null) => {}
+        (Value::Object(lhs), Value::Object(rhs)) => {
+            for (key, value) in rhs {
+                match lhs.get_mut(&key) {
+                    Some(existing) => json(existing, value, policy),
+                    None if value.is_null() => {}
+                    None => {
+                        lhs.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        (Value::Array(lhs), Value::Array(rhs)) => policy.merge(lhs, rhs, |l, r| json(l, r, policy)),
+        (lhs, rhs) => *lhs = rhs,
+    }
+}
+
+#[cfg(feature = "toml")]
+pub(crate) fn toml(lhs: &mut toml::Value, rhs: toml::Value, policy: ArrayPolicy) {
+    use toml::Value;
+
+    match (lhs, rhs) {
+        (Value::Table(lhs), Value::Table(rhs)) => {
+            for (key, value) in rhs {
+                match lhs.get_mut(&key) {
+                    Some(existing) => toml(existing, value, policy),
+                    None => {
+                        lhs.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        (Value::Array(lhs), Value::Array(rhs)) => policy.merge(lhs, rhs, |l, r| toml(l, r, policy)),
+        (lhs, rhs) => *lhs = rhs,
+    }
+}
+
+#[cfg(feature = "serde_yaml_ng")]
+pub(crate) fn yaml(lhs: &mut serde_yaml_ng::Value, rhs: serde_yaml_ng::Value, policy: ArrayPolicy) {
+    use serde_yaml_ng::Value;
+
+    match (lhs, rhs) {
+        (_, Value::Null) => {}
+        (Value::Mapping(lhs), Value::Mapping(rhs)) => {
+            for (key, value) in rhs {
+                match lhs.get_mut(&key) {
+                    Some(existing) => yaml(existing, value, policy),
+                    None if value.is_null() => {}
+                    None => {
+                        lhs.insert(key, value);
+                    }
+                }
+            }
+        }
+
+        (Value::Sequence(lhs), Value::Sequence(rhs)) => policy.merge(lhs, rhs, |l, r| yaml(l, r, policy)),
+        (lhs, rhs) => *lhs = rhs,
+    }
+}
+
+macro_rules! impl_value {
+    ($(
+        $(#[$meta:meta])*
+        $Ty:ty => $merge:ident;
+    )*) => {
+        $(
+            $(#[$meta])*
+            impl Merge for $Ty {
+                fn merge(&mut self, other: Self) {
+                    $merge(self, other, ArrayPolicy::Replace);
+                }
+
+                merge_with_report!(|self, other, report| {
+                    let before = self.clone();
+                    self.merge(other);
+
+                    if *self != before {
+                        report.record();
+                    }
+                });
+            }
+        )*
+    };
+}
+
+impl_value! {
+    /// Objects are merged recursively, arrays are replaced and `null` values from `other` are
+    /// ignored. Check out the [`strategy::json`](super::strategy::json) module for other strategies.
+    #[cfg(feature = "serde_json")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_json")))]
+    serde_json::Value => json;
+
+    /// Tables are merged recursively and arrays are replaced. Check out the
+    /// [`strategy::toml`](super::strategy::toml) module for other strategies.
+    #[cfg(feature = "toml")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "toml")))]
+    toml::Value => toml;
+
+    /// Mappings are merged recursively, sequences are replaced and `null` values from `other`
+    /// are ignored. Check out the [`strategy::yaml`](super::strategy::yaml) module for other strategies.
+    #[cfg(feature = "serde_yaml_ng")]
+    #[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "serde_yaml_ng")))]
+    serde_yaml_ng::Value => yaml;
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn json_values() {
+        use super::{ArrayPolicy, Merge};
+        use serde_json::json;
+
+        let mut value = json!({ "a": 1, "b": { "c": [1, 2], "d": "x" } });
+        value.merge(json!({ "b": { "c": [3], "e": true }, "f": null }));
+
+        assert_eq!(value, json!({ "a": 1, "b": { "c": [3], "d": "x", "e": true } }));
+
+        let mut value = json!([{ "a": 1 }, 2]);
+        super::json(&mut value, json!([{ "b": 2 }, null, 3]), ArrayPolicy::MergeByIndex);
+        assert_eq!(value, json!([{ "a": 1, "b": 2 }, 2, 3]));
+
+        let mut value = json!({ "a": [1] });
+        super::json(&mut value, json!({ "a": [2] }), ArrayPolicy::Append);
+        assert_eq!(value, json!({ "a": [1, 2] }));
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn toml_values() {
+        use super::Merge;
+
+        let mut value: toml::Value = toml::from_str("a = 1\n[b]\nc = [1, 2]\nd = 'x'").unwrap();
+        value.merge(toml::from_str("[b]\nc = [3]\ne = true").unwrap());
+
+        assert_eq!(
+            value,
+            toml::from_str::<toml::Value>("a = 1\n[b]\nc = [3]\nd = 'x'\ne = true").unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde_yaml_ng")]
+    fn yaml_values() {
+        use super::ArrayPolicy;
+
+        let mut value: serde_yaml_ng::Value = serde_yaml_ng::from_str("a: 1\nb:\n  c: [1, 2]").unwrap();
+        super::yaml(
+            &mut value,
+            serde_yaml_ng::from_str("a: ~\nb:\n  c: [3]").unwrap(),
+            ArrayPolicy::Append,
+        );
+
+        assert_eq!(
+            value,
+            serde_yaml_ng::from_str::<serde_yaml_ng::Value>("a: 1\nb:\n  c: [1, 2, 3]").unwrap()
+        );
+    }
+}
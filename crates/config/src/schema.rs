@@ -0,0 +1,536 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Generation of [JSON Schema] documents from configuration types.
+//!
+//! The [`JsonSchema`] trait describes how a type is represented in a configuration
+//! file (`config.toml`, `config.yaml`, etc.) so that tooling like editors or Helm charts
+//! can validate configuration before it reaches the service.
+//!
+//! ***This is also a derive macro when the `macros` feature is avaliable:
+//! <code>#[derive([`JsonSchema`][derive-redirect])]</code>***
+//!
+//! ## Example
+//! ```
+//! use azalia_config::schema::{self, JsonSchema};
+//!
+//! let document = schema::document::<Vec<String>>();
+//! assert_eq!(document["$schema"], schema::DRAFT);
+//! assert_eq!(document["type"], "array");
+//! assert_eq!(document["items"]["type"], "string");
+//! ```
+//!
+//! [JSON Schema]: https://json-schema.org
+//! [derive-redirect]: ../schema/derive.JsonSchema.html
+
+use crate::libstd::{BTreeMap, BTreeSet, Box, Cow, String, ToString, Vec, num};
+use serde_json::json;
+
+pub use serde_json::{Map, Value};
+
+#[cfg(feature = "macros")]
+pub use azalia_config_macros::JsonSchema;
+
+/// The JSON Schema dialect that [`document`] will emit as the `$schema` keyword.
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Trait that describes how a type is represented as a [JSON Schema](https://json-schema.org).
+///
+/// ***This is also a derive macro when the `macros` feature is avaliable:
+/// <code>#[derive([`JsonSchema`][derive-redirect])]</code>***
+///
+/// ## Notes
+/// The derive macro is meant to be used alongside <code>#[derive([`Merge`](crate::merge::Merge))]</code>
+/// and <code>#[derive([`TryFromEnv`](crate::env::TryFromEnv))]</code> on the same struct. The following
+/// attributes are supported with the `schema` namespace:
+///
+/// * `#[schema(crate = <path>)]` (container) — analogous to `#[merge(crate = <path>)]`.
+/// * `#[schema(default)]` (container) — uses `<Self as Default>::default()`, which must also
+///   implement `serde::Serialize`, to fill in the `default` keyword of every field.
+/// * `#[schema(rename_all = "...")]` (container) — renames all fields or variants, supports the same
+///   casing rules as `serde`.
+/// * `#[schema(rename = "...")]` (field, variant) — renames a single field or variant.
+/// * `#[schema(default = <expr>)]` (field) — sets the `default` keyword from any expression.
+/// * `#[schema(skip)]` (field, variant) — skips the field or variant entirely.
+///
+/// Doc comments on the container, fields and variants are emitted as `description`s. Fields are
+/// only marked as `required` if their type is not an [`Option`] and they don't have a default
+/// value from `#[schema(default)]`, `#[serde(default)]` or the container's `#[serde(default)]`.
+///
+/// With the `serde` feature, `#[serde(rename = "...")]` and `#[serde(rename_all = "...")]` are read
+/// as well, and the name that is deserialized from is used if they rename `serialize` and
+/// `deserialize` separately; `#[schema]` renames take precedence over them. Without it, renames
+/// need to be repeated in `#[schema]` so that the property names and the keys that defaults
+/// from `#[schema(default)]` are looked up with match the serialized ones.
+///
+/// Derived types are emitted in `$defs` and referenced with `$ref` when they're used in
+/// another schema, which also makes recursive types like `struct Node { children: Vec<Node> }`
+/// possible.
+///
+/// ## Example
+/// ```
+/// # const _: &str = stringify! {
+/// use azalia_config::schema::JsonSchema;
+///
+/// #[derive(JsonSchema)]
+/// pub struct Config {
+///     /// Port to listen on.
+///     pub port: u16,
+///
+///     /// Host to listen on, defaults to `0.0.0.0`.
+///     pub host: Option<String>,
+/// }
+/// # };
+/// ```
+///
+/// [derive-redirect]: ../schema/derive.JsonSchema.html
+pub trait JsonSchema {
+    /// Name that the schema of `Self` is stored under in `$defs`. Types that return `None`
+    /// are always inlined, derived types are named after the type.
+    fn schema_name() -> Option<Cow<'static, str>> {
+        None
+    }
+
+    /// Returns the schema that describes `Self`. Schemas of the types that `Self` contains
+    /// should be created with [`Definitions::subschema`].
+    fn json_schema_in(defs: &mut Definitions) -> Value;
+
+    /// Returns the schema that describes `Self`, with the schemas of named types that it
+    /// contains in `$defs`.
+    fn json_schema() -> Value {
+        Definitions::root::<Self>()
+    }
+
+    /// Whether if a field of this type can be omitted. This is only `true` for
+    /// <code>[`Option`]\<T\></code>.
+    fn optional() -> bool {
+        false
+    }
+}
+
+/// Schemas of named types that were collected while creating a schema, which are emitted
+/// in `$defs` and referenced with `$ref`, so that recursive types have a finite schema.
+#[derive(Debug, Default)]
+pub struct Definitions {
+    /// the root type, which is referenced as `#`.
+    root: Option<&'static str>,
+
+    /// [`type_name`](core::any::type_name) of the types that were defined, to their name in `$defs`.
+    names: BTreeMap<&'static str, String>,
+    defs: Map<String, Value>,
+}
+
+impl Definitions {
+    /// Returns the schema of `T` as a root schema, with the schemas of named types in `$defs`.
+    pub fn root<T: JsonSchema + ?Sized>() -> Value {
+        let mut defs = Definitions::default();
+        if T::schema_name().is_some() {
+            defs.root = Some(core::any::type_name::<T>());
+        }
+
+        let mut schema = T::json_schema_in(&mut defs);
+        if !defs.defs.is_empty()
+            && let Value::Object(ref mut object) = schema
+        {
+            object.insert(String::from("$defs"), Value::Object(defs.defs));
+        }
+
+        schema
+    }
+
+    /// Returns the schema of `T` to use in another schema. This is a `$ref` if `T` has a
+    /// [name](JsonSchema::schema_name), which defines `T` the first time that it is used.
+    pub fn subschema<T: JsonSchema + ?Sized>(&mut self) -> Value {
+        let Some(name) = T::schema_name() else {
+            return T::json_schema_in(self);
+        };
+
+        let id = core::any::type_name::<T>();
+        if self.root == Some(id) {
+            return json!({ "$ref": "#" });
+        }
+
+        if let Some(name) = self.names.get(id) {
+            return reference(name);
+        }
+
+        // different types can have the same name, like generic types or types
+        // with the same name in different modules
+        let mut unique = String::from(&*name);
+        let mut idx = 1;
+        while self.names.values().any(|defined| *defined == unique) {
+            idx += 1;
+            unique = String::from(&*name);
+            unique.push_str(&idx.to_string());
+        }
+
+        // the name is registered first, so that `T` is referenced when it contains itself
+        self.names.insert(id, unique.clone());
+        let schema = T::json_schema_in(self);
+        self.defs.insert(unique.clone(), schema);
+
+        reference(&unique)
+    }
+}
+
+fn reference(name: &str) -> Value {
+    let mut pointer = String::from("#/$defs/");
+    for ch in name.chars() {
+        match ch {
+            '~' => pointer.push_str("~0"),
+            '/' => pointer.push_str("~1"),
+            ch => pointer.push(ch),
+        }
+    }
+
+    json!({ "$ref": pointer })
+}
+
+/// Returns the schema of `T` as a root document with the `$schema` keyword set to [`DRAFT`].
+pub fn document<T: JsonSchema + ?Sized>() -> Value {
+    let mut schema = T::json_schema();
+    if let Value::Object(ref mut object) = schema {
+        object.insert(String::from("$schema"), Value::from(DRAFT));
+    }
+
+    schema
+}
+
+impl JsonSchema for () {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "null" })
+    }
+}
+
+impl JsonSchema for bool {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "boolean" })
+    }
+}
+
+impl JsonSchema for char {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string", "minLength": 1, "maxLength": 1 })
+    }
+}
+
+impl JsonSchema for str {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl JsonSchema for String {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "anyOf": [defs.subschema::<T>(), { "type": "null" }] })
+    }
+
+    fn optional() -> bool {
+        true
+    }
+}
+
+impl<T: JsonSchema + ?Sized> JsonSchema for Box<T> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        defs.subschema::<T>()
+    }
+
+    fn optional() -> bool {
+        T::optional()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "type": "array", "items": defs.subschema::<T>() })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for BTreeSet<T> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "type": "array", "items": defs.subschema::<T>(), "uniqueItems": true })
+    }
+}
+
+/// Maps are represented as objects, so `K` must serialize as a string.
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "type": "object", "additionalProperties": defs.subschema::<V>() })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+impl<T: JsonSchema, S> JsonSchema for std::collections::HashSet<T, S> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "type": "array", "items": defs.subschema::<T>(), "uniqueItems": true })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+/// Maps are represented as objects, so `K` must serialize as a string.
+impl<K, V: JsonSchema, S> JsonSchema for std::collections::HashMap<K, V, S> {
+    fn json_schema_in(defs: &mut Definitions) -> Value {
+        json!({ "type": "object", "additionalProperties": defs.subschema::<V>() })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "std")))]
+impl JsonSchema for std::path::PathBuf {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string" })
+    }
+}
+
+#[cfg(feature = "url")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "url")))]
+impl JsonSchema for url::Url {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string", "format": "uri" })
+    }
+}
+
+#[cfg(feature = "sentry")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "sentry")))]
+impl JsonSchema for sentry_types::Dsn {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string", "format": "uri" })
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[cfg_attr(any(noeldoc, docsrs), doc(cfg(feature = "tracing")))]
+impl JsonSchema for tracing::Level {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "string", "enum": ["trace", "debug", "info", "warn", "error"] })
+    }
+}
+
+impl JsonSchema for f32 {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "number" })
+    }
+}
+
+impl JsonSchema for f64 {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "number" })
+    }
+}
+
+macro_rules! impl_integers {
+    ($($Ty:ty)*) => {
+        $(impl JsonSchema for $Ty {
+            fn json_schema_in(_: &mut Definitions) -> Value {
+                json!({ "type": "integer", "minimum": <$Ty>::MIN, "maximum": <$Ty>::MAX })
+            }
+        })*
+    };
+}
+
+// 128-bit integers can't be represented as a JSON number, so only the sign is kept.
+impl_integers!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl JsonSchema for i128 {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "integer" })
+    }
+}
+
+impl JsonSchema for u128 {
+    fn json_schema_in(_: &mut Definitions) -> Value {
+        json!({ "type": "integer", "minimum": 0 })
+    }
+}
+
+macro_rules! impl_nonzero {
+    (signed: $($Signed:ty)*; unsigned: $($Unsigned:ty)*;) => {
+        $(impl JsonSchema for $Signed {
+            fn json_schema_in(_: &mut Definitions) -> Value {
+                json!({ "type": "integer", "not": { "const": 0 } })
+            }
+        })*
+
+        $(impl JsonSchema for $Unsigned {
+            fn json_schema_in(_: &mut Definitions) -> Value {
+                json!({ "type": "integer", "minimum": 1 })
+            }
+        })*
+    };
+}
+
+impl_nonzero!(
+    signed:
+        num::NonZeroI8 num::NonZeroI16 num::NonZeroI32
+        num::NonZeroI64 num::NonZeroI128 num::NonZeroIsize;
+    unsigned:
+        num::NonZeroU8 num::NonZeroU16 num::NonZeroU32
+        num::NonZeroU64 num::NonZeroU128 num::NonZeroUsize;
+);
+
+#[doc(hidden)]
+pub mod __private {
+    //! Helpers used by `#[derive(JsonSchema)]`. This is not public API.
+
+    use super::{Map, Value};
+    use crate::libstd::{String, Vec};
+
+    pub use crate::libstd::Cow;
+    pub use serde_json::to_value;
+
+    /// Sets `keyword` on `schema` if it is an object.
+    pub fn annotate(schema: &mut Value, keyword: &str, value: Value) {
+        if let Value::Object(object) = schema {
+            object.insert(String::from(keyword), value);
+        }
+    }
+
+    /// Returns the serialized `Self::default()` as an object, or an empty map if it
+    /// failed to serialize or isn't represented as one.
+    pub fn defaults(value: Result<Value, serde_json::Error>) -> Map<String, Value> {
+        match value {
+            Ok(Value::Object(object)) => object,
+            _ => Map::new(),
+        }
+    }
+
+    /// Builds a `{"type": "object"}` schema from a list of `(name, schema, required)` properties.
+    pub fn object(properties: impl IntoIterator<Item = (&'static str, Value, bool)>) -> Value {
+        let mut props = Map::new();
+        let mut required = Vec::new();
+
+        for (name, schema, is_required) in properties {
+            if is_required {
+                required.push(Value::from(name));
+            }
+
+            props.insert(String::from(name), schema);
+        }
+
+        let mut object = Map::new();
+        object.insert(String::from("type"), Value::from("object"));
+        object.insert(String::from("properties"), Value::Object(props));
+        object.insert(String::from("additionalProperties"), Value::Bool(false));
+        if !required.is_empty() {
+            object.insert(String::from("required"), Value::Array(required));
+        }
+
+        Value::Object(object)
+    }
+
+    /// Builds a fixed-length array schema for tuple structs and tuple variants.
+    pub fn tuple(items: impl IntoIterator<Item = Value>) -> Value {
+        let items = items.into_iter().collect::<Vec<_>>();
+        let len = items.len();
+        let mut object = Map::new();
+
+        object.insert(String::from("type"), Value::from("array"));
+        object.insert(String::from("prefixItems"), Value::Array(items));
+        object.insert(String::from("minItems"), Value::from(len));
+        object.insert(String::from("maxItems"), Value::from(len));
+
+        Value::Object(object)
+    }
+
+    /// Builds the schema of a externally tagged enum variant that holds data.
+    pub fn tagged(name: &'static str, schema: Value) -> Value {
+        object([(name, schema, true)])
+    }
+
+    /// Builds the schema of a unit variant.
+    pub fn constant(name: &'static str) -> Value {
+        let mut object = Map::new();
+        object.insert(String::from("const"), Value::from(name));
+
+        Value::Object(object)
+    }
+
+    /// Builds the schema of a enum that only has unit variants.
+    pub fn enumeration(names: impl IntoIterator<Item = &'static str>) -> Value {
+        let mut object = Map::new();
+        object.insert(String::from("type"), Value::from("string"));
+        object.insert(
+            String::from("enum"),
+            Value::Array(names.into_iter().map(Value::from).collect()),
+        );
+
+        Value::Object(object)
+    }
+
+    /// Builds the schema of a enum where exactly one of the variants' schemas must match.
+    pub fn one_of(variants: impl IntoIterator<Item = Value>) -> Value {
+        let mut object = Map::new();
+        object.insert(String::from("oneOf"), Value::Array(variants.into_iter().collect()));
+
+        Value::Object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        assert_eq!(bool::json_schema(), json!({ "type": "boolean" }));
+        assert_eq!(
+            u8::json_schema(),
+            json!({ "type": "integer", "minimum": 0, "maximum": 255 })
+        );
+
+        assert_eq!(num::NonZeroU16::json_schema()["minimum"], 1);
+        assert_eq!(String::json_schema(), json!({ "type": "string" }));
+    }
+
+    #[test]
+    fn optional_values() {
+        assert!(<Option<u32>>::optional());
+        assert!(!u32::optional());
+        assert_eq!(
+            <Option<bool>>::json_schema(),
+            json!({ "anyOf": [{ "type": "boolean" }, { "type": "null" }] })
+        );
+    }
+
+    #[test]
+    fn collections() {
+        assert_eq!(
+            <BTreeMap<String, Vec<bool>>>::json_schema(),
+            json!({
+                "type": "object",
+                "additionalProperties": {
+                    "type": "array",
+                    "items": { "type": "boolean" }
+                }
+            })
+        );
+
+        assert_eq!(<BTreeSet<String>>::json_schema()["uniqueItems"], true);
+    }
+}
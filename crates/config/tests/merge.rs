@@ -0,0 +1,1136 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(feature = "macros")]
+
+use azalia_config::merge::{Layers, Merge, Merge3, MergeConflict, MergeReport, Partial, TryMerge};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    num::NonZeroU64,
+};
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Database {
+    url: Option<String>,
+    replicas: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Config {
+    #[merge(deep)]
+    databases: BTreeMap<String, Database>,
+
+    #[merge(deep)]
+    caches: HashMap<String, Vec<u32>>,
+}
+
+#[test]
+fn deep_maps() {
+    let mut config = Config {
+        databases: BTreeMap::from([(
+            String::from("primary"),
+            Database {
+                url: Some(String::from("postgres://localhost")),
+                replicas: vec![String::from("a")],
+            },
+        )]),
+
+        caches: HashMap::from([(String::from("redis"), vec![1])]),
+    };
+
+    config.merge(Config {
+        databases: BTreeMap::from([
+            (
+                String::from("primary"),
+                Database {
+                    url: None,
+                    replicas: vec![String::from("b")],
+                },
+            ),
+            (String::from("secondary"), Database::default()),
+        ]),
+
+        caches: HashMap::from([(String::from("redis"), vec![2])]),
+    });
+
+    assert_eq!(
+        config.databases["primary"],
+        Database {
+            url: Some(String::from("postgres://localhost")),
+            replicas: vec![String::from("a"), String::from("b")],
+        }
+    );
+
+    assert_eq!(config.databases["secondary"], Database::default());
+    assert_eq!(config.caches["redis"], vec![1, 2]);
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+enum Storage {
+    Filesystem(Vec<String>),
+    S3 {
+        bucket: Option<String>,
+        region: Option<String>,
+
+        #[merge(skip)]
+        prefix: Option<String>,
+    },
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+enum Auth {
+    None,
+    Ldap(#[merge(strategy = azalia_config::merge::strategy::string::overwrite)] String),
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config, strategy = keep)]
+enum Keep {
+    A,
+    B,
+}
+
+fn keep(_: &mut Keep, _: Keep) {}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config)]
+enum Empty {}
+
+#[test]
+fn enumerations() {
+    let mut storage = Storage::Filesystem(vec![String::from("/data")]);
+    storage.merge(Storage::Filesystem(vec![String::from("/backup")]));
+    assert_eq!(
+        storage,
+        Storage::Filesystem(vec![String::from("/data"), String::from("/backup")])
+    );
+
+    storage.merge(Storage::S3 {
+        bucket: Some(String::from("bucket")),
+        region: None,
+        prefix: None,
+    });
+
+    storage.merge(Storage::S3 {
+        bucket: Some(String::from("other")),
+        region: Some(String::from("us-east-1")),
+        prefix: Some(String::from("ignored")),
+    });
+
+    assert_eq!(
+        storage,
+        Storage::S3 {
+            bucket: Some(String::from("bucket")),
+            region: Some(String::from("us-east-1")),
+            prefix: None,
+        }
+    );
+
+    let mut auth = Auth::None;
+    auth.merge(Auth::Ldap(String::from("ldap://a")));
+    auth.merge(Auth::Ldap(String::from("ldap://b")));
+    assert_eq!(auth, Auth::Ldap(String::from("ldap://b")));
+
+    auth.merge(Auth::None);
+    assert_eq!(auth, Auth::None);
+
+    let mut value = Keep::A;
+    value.merge(Keep::B);
+    assert_eq!(value, Keep::A);
+
+    fn _assert_merge<T: Merge>() {}
+    _assert_merge::<Empty>();
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config, default)]
+struct Server {
+    enabled: bool,
+    port: u16,
+    ratio: f64,
+
+    #[merge(default = "fill")]
+    name: String,
+
+    // explicit strategies take precedence over the container's mode
+    #[merge(strategy = azalia_config::merge::strategy::vec::overwrite)]
+    hosts: Vec<String>,
+}
+
+#[test]
+fn default_aware() {
+    let mut server = Server {
+        enabled: true,
+        port: 8080,
+        ratio: 0.5,
+        name: String::from("base"),
+        hosts: vec![String::from("a")],
+    };
+
+    server.merge(Server {
+        enabled: false,
+        port: 0,
+        ratio: -1.0,
+        name: String::from("overlay"),
+        hosts: vec![String::from("b")],
+    });
+
+    assert_eq!(
+        server,
+        Server {
+            enabled: true,
+            port: 8080,
+            ratio: -1.0,
+            name: String::from("base"),
+            hosts: vec![String::from("b")],
+        }
+    );
+}
+
+#[derive(Debug, Default, Merge)]
+#[merge(crate = azalia_config, default)]
+struct Service {
+    enabled: bool,
+    name: Option<String>,
+
+    // neither of these implement `PartialEq`, so they can only be merged recursively
+    server: Unequal,
+    servers: Vec<Unequal>,
+    labels: BTreeMap<String, Unequal>,
+}
+
+#[derive(Debug, Default, Merge)]
+#[merge(crate = azalia_config)]
+struct Unequal {
+    port: u16,
+    host: String,
+}
+
+#[test]
+fn container_default_recurses() {
+    let unequal = |port: u16, host: &str| Unequal {
+        port,
+        host: String::from(host),
+    };
+
+    let mut service = Service {
+        enabled: true,
+        name: Some(String::from("base")),
+        server: unequal(8080, "localhost"),
+        servers: vec![unequal(1, "a")],
+        labels: BTreeMap::from([(String::from("a"), unequal(1, "a"))]),
+    };
+
+    service.merge(Service {
+        enabled: false,
+        name: None,
+        server: unequal(0, "example.com"),
+        servers: vec![unequal(2, "b")],
+        labels: BTreeMap::from([(String::from("b"), unequal(2, "b"))]),
+    });
+
+    assert!(service.enabled);
+    assert_eq!(service.name.as_deref(), Some("base"));
+
+    // the nested struct was merged field by field rather than being replaced
+    assert_eq!(service.server.port, 8080);
+    assert_eq!(service.server.host, "example.com");
+    assert_eq!(service.servers.len(), 2);
+    assert_eq!(service.labels.len(), 2);
+}
+
+#[derive(Debug, Clone, Default, PartialEq, TryMerge)]
+#[merge(crate = azalia_config)]
+struct Strict {
+    database: StrictDatabase,
+    replicas: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, TryMerge)]
+#[merge(crate = azalia_config)]
+struct StrictDatabase {
+    #[merge(conflict = "error")]
+    url: String,
+
+    #[merge(try_strategy = max_pool)]
+    pool: u32,
+}
+
+fn max_pool(lhs: &mut u32, rhs: u32) -> Result<(), MergeConflict> {
+    if rhs > 64 {
+        return Err(MergeConflict::new().with_reason("pool size is too large"));
+    }
+
+    *lhs = rhs;
+    Ok(())
+}
+
+#[test]
+fn try_merge() {
+    let mut strict = Strict::default();
+    strict
+        .try_merge(Strict {
+            database: StrictDatabase {
+                url: String::from("postgres://a"),
+                pool: 8,
+            },
+            replicas: vec![String::from("a")],
+        })
+        .unwrap();
+
+    // same value in both layers doesn't conflict
+    strict
+        .try_merge(Strict {
+            database: StrictDatabase {
+                url: String::from("postgres://a"),
+                pool: 16,
+            },
+            replicas: vec![String::from("b")],
+        })
+        .unwrap();
+
+    assert_eq!(strict.database.pool, 16);
+    assert_eq!(strict.replicas, [String::from("a"), String::from("b")]);
+
+    let conflict = strict
+        .try_merge(Strict {
+            database: StrictDatabase {
+                url: String::from("postgres://b"),
+                pool: 16,
+            },
+            replicas: vec![],
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "database.url");
+    assert_eq!(strict.database.url, "postgres://a");
+
+    let conflict = strict
+        .try_merge(Strict {
+            database: StrictDatabase {
+                url: String::new(),
+                pool: 128,
+            },
+            replicas: vec![],
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "database.pool");
+    assert_eq!(
+        conflict.to_string(),
+        "conflicting values for `database.pool`: pool size is too large"
+    );
+}
+
+#[test]
+fn try_merge_partial_state() {
+    let base = Strict {
+        database: StrictDatabase {
+            url: String::new(),
+            pool: 8,
+        },
+        replicas: vec![String::from("a")],
+    };
+
+    let overlay = || Strict {
+        database: StrictDatabase {
+            url: String::from("postgres://a"),
+            pool: 128,
+        },
+        replicas: vec![String::from("b")],
+    };
+
+    // `url` was merged before `pool` conflicted, and `replicas` was never merged
+    let mut strict = base.clone();
+    assert_eq!(strict.try_merge(overlay()).unwrap_err().path(), "database.pool");
+    assert_eq!(strict.database.url, "postgres://a");
+    assert_eq!(strict.database.pool, 8);
+    assert_eq!(strict.replicas, [String::from("a")]);
+
+    let mut strict = base.clone();
+    assert!(strict.try_merge_or_rollback(overlay()).is_err());
+    assert_eq!(strict, base);
+
+    let conflict = Strict::try_merge_all([base.clone(), overlay()]).unwrap_err();
+    assert_eq!(conflict.path(), "database.pool");
+    assert_eq!(Strict::try_merge_all([]), Ok(None));
+    assert_eq!(Strict::try_merge_all([base.clone()]), Ok(Some(base)));
+}
+
+#[derive(Debug, PartialEq, TryMerge)]
+#[merge(crate = azalia_config)]
+enum StrictStorage {
+    Filesystem {
+        #[merge(conflict = "error")]
+        directory: String,
+    },
+
+    Memory,
+}
+
+#[test]
+fn try_merge_enumerations() {
+    let mut storage = StrictStorage::Filesystem {
+        directory: String::from("/data"),
+    };
+
+    let conflict = storage
+        .try_merge(StrictStorage::Filesystem {
+            directory: String::from("/var/data"),
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "directory");
+
+    storage.try_merge(StrictStorage::Memory).unwrap();
+    assert_eq!(storage, StrictStorage::Memory);
+}
+
+#[derive(Debug, Default, PartialEq, TryMerge)]
+#[merge(crate = azalia_config, recurse_options)]
+struct StrictService {
+    primary: Option<StrictDatabase>,
+    fallback: Box<StrictDatabase>,
+    shards: [StrictDatabase; 2],
+    regions: BTreeMap<String, StrictDatabase>,
+    database: Database,
+}
+
+fn strict_database(url: &str) -> StrictDatabase {
+    StrictDatabase {
+        url: String::from(url),
+        pool: 0,
+    }
+}
+
+#[test]
+fn try_merge_containers() {
+    let service = || StrictService {
+        primary: Some(strict_database("postgres://a")),
+        fallback: Box::new(strict_database("postgres://a")),
+        shards: [strict_database("postgres://a"), strict_database("postgres://a")],
+        regions: BTreeMap::from([(String::from("eu"), strict_database("postgres://a"))]),
+        database: Database {
+            url: Some(String::from("postgres://a")),
+            replicas: vec![String::from("a")],
+        },
+    };
+
+    let conflict = service()
+        .try_merge(StrictService {
+            primary: Some(strict_database("postgres://b")),
+            ..Default::default()
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "primary.url");
+
+    let conflict = service()
+        .try_merge(StrictService {
+            fallback: Box::new(strict_database("postgres://b")),
+            ..Default::default()
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "fallback.url");
+
+    let conflict = service()
+        .try_merge(StrictService {
+            shards: [strict_database(""), strict_database("postgres://b")],
+            ..Default::default()
+        })
+        .unwrap_err();
+
+    assert_eq!(conflict.path(), "shards.1.url");
+
+    // maps and `Merge` types merge like their `Merge` implementation, which never fails
+    let mut service = service();
+    service
+        .try_merge(StrictService {
+            regions: BTreeMap::from([(String::from("eu"), strict_database("postgres://b"))]),
+            database: Database {
+                url: Some(String::from("postgres://b")),
+                replicas: vec![String::from("b")],
+            },
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(service.regions["eu"].url, "postgres://b");
+    assert_eq!(service.database.url.as_deref(), Some("postgres://a"));
+    assert_eq!(service.database.replicas, [String::from("a"), String::from("b")]);
+
+    let mut layers = Layers::new();
+    layers.push(Some(strict_database("postgres://a")));
+    layers.push(None);
+    assert_eq!(layers.try_effective(), Ok(Some(Some(strict_database("postgres://a")))));
+
+    let layers = Layers::from(vec![
+        vec![strict_database("postgres://a")],
+        vec![strict_database("postgres://b")],
+    ]);
+    assert_eq!(layers.try_effective().unwrap().unwrap().len(), 2);
+
+    let layers = Layers::from(vec![strict_database("postgres://a"), strict_database("postgres://b")]);
+    assert_eq!(layers.try_effective().unwrap_err().path(), "url");
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Layered {
+    server: LayeredServer,
+    logging: LayeredLogging,
+    storage: LayeredStorage,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+enum LayeredStorage {
+    Filesystem {
+        directory: String,
+    },
+
+    #[default]
+    Memory,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct LayeredServer {
+    #[merge(default)]
+    host: String,
+    port: u16,
+    r#type: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct LayeredLogging {
+    level: String,
+    json: bool,
+}
+
+#[test]
+fn merge_with_report() {
+    let mut config = Layered {
+        server: LayeredServer {
+            host: String::from("localhost"),
+            port: 8080,
+            r#type: None,
+        },
+        logging: LayeredLogging {
+            level: String::from("info"),
+            json: false,
+        },
+        storage: LayeredStorage::Filesystem {
+            directory: String::from("/data"),
+        },
+    };
+
+    let mut report = MergeReport::new();
+    config.merge_with_report(
+        Layered {
+            server: LayeredServer {
+                host: String::new(),
+                port: 3000,
+                r#type: Some(String::from("http")),
+            },
+            logging: LayeredLogging {
+                level: String::from("debug"),
+                json: false,
+            },
+            storage: LayeredStorage::Filesystem {
+                directory: String::from("/data"),
+            },
+        },
+        &mut report,
+    );
+
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(report.changed(), ["server.port", "server.type", "logging.level"]);
+    assert_eq!(report.to_string(), "server.port, server.type, logging.level");
+
+    let mut report = MergeReport::new();
+    config.merge_with_report(
+        Layered {
+            storage: LayeredStorage::Memory,
+            ..Default::default()
+        },
+        &mut report,
+    );
+
+    // `logging.level` is a `String` that only compares, so an empty value is a change
+    assert_eq!(report.changed(), ["logging.level", "storage"]);
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config, strategy_for(Vec) = azalia_config::merge::strategy::vec::overwrite)]
+struct Strategies {
+    #[merge(strategy = azalia_config::merge::strategy::numeric::max)]
+    workers: u16,
+
+    #[merge(deep)]
+    upstreams: BTreeMap<String, Vec<String>>,
+    origins: Vec<String>,
+
+    #[merge(strategy = replace)]
+    opaque: Opaque,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Opaque(u8);
+
+// doesn't implement `Clone`, so it can't be snapshotted
+impl Merge for Opaque {
+    fn merge(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+fn replace(lhs: &mut Opaque, rhs: Opaque) {
+    *lhs = rhs;
+}
+
+#[test]
+fn merge_with_report_strategies() {
+    let mut config = Strategies {
+        workers: 4,
+        upstreams: BTreeMap::from([(String::from("api"), vec![String::from("10.0.0.1")])]),
+        origins: vec![String::from("https://noelware.org")],
+        opaque: Opaque(1),
+    };
+
+    let mut report = MergeReport::new();
+    config.merge_with_report(
+        Strategies {
+            workers: 8,
+            upstreams: BTreeMap::from([(String::from("api"), vec![String::from("10.0.0.2")])]),
+            origins: vec![String::from("https://noelware.org")],
+            opaque: Opaque(2),
+        },
+        &mut report,
+    );
+
+    assert_eq!(config.workers, 8);
+    assert_eq!(config.opaque, Opaque(2));
+    assert_eq!(report.changed(), ["workers", "upstreams"]);
+
+    let mut report = MergeReport::new();
+    config.merge_with_report(
+        Strategies {
+            workers: 2,
+            upstreams: BTreeMap::from([(String::from("cdn"), Vec::new())]),
+            origins: Vec::new(),
+            opaque: Opaque(2),
+        },
+        &mut report,
+    );
+
+    assert_eq!(config.workers, 8);
+    assert_eq!(report.changed(), ["upstreams", "origins"]);
+
+    let mut report = MergeReport::new();
+    config.merge_with_report(
+        Strategies {
+            workers: 8,
+            upstreams: BTreeMap::new(),
+            origins: Vec::new(),
+            opaque: Opaque(3),
+        },
+        &mut report,
+    );
+
+    assert!(report.is_empty());
+}
+
+#[derive(Debug, Clone, PartialEq, Merge)]
+#[merge(crate = azalia_config, strategy = replace_profile)]
+enum Profile {
+    Debug,
+    Release { lto: bool },
+}
+
+fn replace_profile(lhs: &mut Profile, rhs: Profile) {
+    *lhs = rhs;
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Build {
+    profile: Profile,
+}
+
+#[test]
+fn merge_with_report_enum_strategy() {
+    let mut build = Build {
+        profile: Profile::Debug,
+    };
+
+    let mut report = MergeReport::new();
+    build.merge_with_report(
+        Build {
+            profile: Profile::Release { lto: true },
+        },
+        &mut report,
+    );
+
+    assert_eq!(build.profile, Profile::Release { lto: true });
+    assert_eq!(report.changed(), ["profile"]);
+
+    let mut report = MergeReport::new();
+    build.merge_with_report(
+        Build {
+            profile: Profile::Release { lto: true },
+        },
+        &mut report,
+    );
+
+    assert!(report.is_empty());
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config, partial(derive(Debug, PartialEq)))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize), serde(rename_all = "camelCase"))]
+struct Overlayed {
+    name: String,
+    log_level: Option<String>,
+
+    #[merge(partial)]
+    server: OverlayedServer,
+
+    #[merge(skip)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    secret: String,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config, partial(name = ServerOverlay, derive(Debug, PartialEq)))]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+struct OverlayedServer {
+    host: String,
+
+    #[cfg_attr(feature = "serde", serde(alias = "p"))]
+    port: u16,
+}
+
+#[test]
+fn partials() {
+    let mut overlay = PartialOverlayed::default();
+    overlay.merge(PartialOverlayed {
+        name: Some(String::from("a")),
+        server: ServerOverlay {
+            port: Some(3000),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    overlay.merge(PartialOverlayed {
+        name: Some(String::from("b")),
+        log_level: Some(String::from("debug")),
+        ..Default::default()
+    });
+
+    assert_eq!(
+        overlay,
+        PartialOverlayed {
+            name: Some(String::from("b")),
+            log_level: Some(String::from("debug")),
+            server: ServerOverlay {
+                host: None,
+                port: Some(3000),
+            },
+        }
+    );
+
+    let mut config = Overlayed {
+        name: String::from("base"),
+        log_level: None,
+        server: OverlayedServer {
+            host: String::from("localhost"),
+            port: 8080,
+        },
+        secret: String::from("hunter2"),
+    };
+
+    config.apply(overlay);
+    assert_eq!(
+        config,
+        Overlayed {
+            name: String::from("b"),
+            log_level: Some(String::from("debug")),
+            server: OverlayedServer {
+                host: String::from("localhost"),
+                port: 3000,
+            },
+            secret: String::from("hunter2"),
+        }
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn partials_deserialize() {
+    let overlay: PartialOverlayed = serde_json::from_str(r#"{"logLevel":"trace","server":{"p":3000}}"#).unwrap();
+    assert_eq!(
+        overlay,
+        PartialOverlayed {
+            name: None,
+            log_level: Some(String::from("trace")),
+            server: ServerOverlay {
+                host: None,
+                port: Some(3000),
+            },
+        }
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Merge, Merge3)]
+#[merge(crate = azalia_config)]
+struct Reloadable {
+    name: String,
+    features: BTreeSet<String>,
+    limits: BTreeMap<String, u32>,
+    mode: Mode,
+
+    #[merge(skip)]
+    generation: u64,
+
+    #[merge(three_way = azalia_config::merge::strategy::three_way::ours)]
+    pinned: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Merge, Merge3)]
+#[merge(crate = azalia_config)]
+enum Mode {
+    Standalone { workers: u32, name: String },
+    Clustered(Vec<String>),
+}
+
+#[test]
+fn three_way_merge() {
+    let base = Reloadable {
+        name: String::from("azalia"),
+        features: BTreeSet::from([String::from("a"), String::from("b")]),
+        limits: BTreeMap::from([(String::from("requests"), 100), (String::from("uploads"), 10)]),
+        mode: Mode::Standalone {
+            workers: 4,
+            name: String::from("primary"),
+        },
+        generation: 1,
+        pinned: 1,
+    };
+
+    // changed at runtime through an admin API
+    let mut ours = base.clone();
+    ours.name = String::from("runtime");
+    ours.features.insert(String::from("c"));
+    ours.limits.insert(String::from("uploads"), 20);
+    ours.generation = 2;
+    ours.mode = Mode::Standalone {
+        workers: 8,
+        name: String::from("primary"),
+    };
+
+    // changed in the file
+    let mut theirs = base.clone();
+    theirs.features.remove("a");
+    theirs.limits.insert(String::from("requests"), 200);
+    theirs.generation = 3;
+    theirs.pinned = 3;
+    theirs.mode = Mode::Standalone {
+        workers: 4,
+        name: String::from("secondary"),
+    };
+
+    ours.merge3(base.clone(), theirs);
+    assert_eq!(
+        ours,
+        Reloadable {
+            name: String::from("runtime"),
+            features: BTreeSet::from([String::from("b"), String::from("c")]),
+            limits: BTreeMap::from([(String::from("requests"), 200), (String::from("uploads"), 20)]),
+            mode: Mode::Standalone {
+                workers: 8,
+                name: String::from("secondary"),
+            },
+            generation: 2,
+            pinned: 1,
+        }
+    );
+
+    // a different variant from the file always wins
+    let mut theirs = base.clone();
+    theirs.mode = Mode::Clustered(vec![String::from("node-1")]);
+
+    ours.merge3(base, theirs);
+    assert_eq!(ours.mode, Mode::Clustered(vec![String::from("node-1")]));
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Gateway {
+    #[merge(strategy = azalia_config::merge::strategy::vec::merge_by_key(|upstream: &Upstream| upstream.name.clone()))]
+    upstreams: Vec<Upstream>,
+
+    #[merge(strategy = azalia_config::merge::strategy::vec::union)]
+    plugins: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Upstream {
+    name: String,
+    weight: Option<u32>,
+}
+
+#[test]
+fn collection_strategies() {
+    let mut gateway = Gateway {
+        upstreams: vec![Upstream {
+            name: String::from("api"),
+            weight: None,
+        }],
+        plugins: vec![String::from("auth")],
+    };
+
+    gateway.merge(Gateway {
+        upstreams: vec![
+            Upstream {
+                name: String::from("api"),
+                weight: Some(10),
+            },
+            Upstream {
+                name: String::from("web"),
+                weight: None,
+            },
+        ],
+        plugins: vec![String::from("metrics"), String::from("auth")],
+    });
+
+    assert_eq!(
+        gateway,
+        Gateway {
+            upstreams: vec![
+                Upstream {
+                    name: String::from("api"),
+                    weight: Some(10),
+                },
+                Upstream {
+                    name: String::from("web"),
+                    weight: None,
+                },
+            ],
+            plugins: vec![String::from("auth"), String::from("metrics")],
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Limits {
+    #[merge(strategy = azalia_config::merge::strategy::numeric::max)]
+    max_connections: u32,
+
+    #[merge(strategy = azalia_config::merge::strategy::numeric::min)]
+    max_body_size: NonZeroU64,
+
+    #[merge(strategy = azalia_config::merge::strategy::numeric::clamp(1, 16))]
+    workers: usize,
+}
+
+#[test]
+fn numeric_strategies() {
+    let mut limits = Limits {
+        max_connections: 100,
+        max_body_size: NonZeroU64::new(1024).unwrap(),
+        workers: 4,
+    };
+
+    limits.merge(Limits {
+        max_connections: 50,
+        max_body_size: NonZeroU64::new(4096).unwrap(),
+        workers: 64,
+    });
+
+    assert_eq!(
+        limits,
+        Limits {
+            max_connections: 100,
+            max_body_size: NonZeroU64::new(1024).unwrap(),
+            workers: 16,
+        }
+    );
+}
+
+#[test]
+fn clamp_ignores_nan() {
+    let clamp = azalia_config::merge::strategy::numeric::clamp(0.0, 1.0);
+    let mut ratio = 0.5f64;
+
+    clamp(&mut ratio, f64::NAN);
+    assert_eq!(ratio, 0.5);
+
+    clamp(&mut ratio, 2.0);
+    assert_eq!(ratio, 1.0);
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Wrapper<T> {
+    inner: T,
+    names: Vec<T>,
+
+    #[merge(skip)]
+    marker: core::marker::PhantomData<T>,
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config, bound = "T: Default + PartialEq")]
+struct Bounded<T> {
+    #[merge(strategy = azalia_config::merge::strategy::default::overwrite)]
+    value: T,
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(
+    crate = azalia_config,
+    strategy = azalia_config::merge::strategy::numeric::max,
+    strategy_for(Vec) = azalia_config::merge::strategy::vec::overwrite
+)]
+struct Defaults {
+    a: u32,
+    b: u64,
+    hosts: Vec<String>,
+
+    #[merge(strategy = azalia_config::merge::strategy::vec::append)]
+    ports: Vec<u16>,
+}
+
+#[test]
+fn container_strategies_and_bounds() {
+    let mut layered = Wrapper {
+        inner: 1u32,
+        names: vec![1],
+        marker: core::marker::PhantomData,
+    };
+
+    layered.merge(Wrapper {
+        inner: 2,
+        names: vec![2],
+        marker: core::marker::PhantomData,
+    });
+
+    assert_eq!(layered.inner, 2);
+    assert_eq!(layered.names, vec![1, 2]);
+
+    let mut bounded = Bounded {
+        value: String::from("a"),
+    };
+    bounded.merge(Bounded {
+        value: String::from("b"),
+    });
+    assert_eq!(bounded.value, "b");
+
+    let mut defaults = Defaults {
+        a: 10,
+        b: 1,
+        hosts: vec![String::from("a")],
+        ports: vec![80],
+    };
+
+    defaults.merge(Defaults {
+        a: 5,
+        b: 20,
+        hosts: vec![String::from("b")],
+        ports: vec![443],
+    });
+
+    assert_eq!(
+        defaults,
+        Defaults {
+            a: 10,
+            b: 20,
+            hosts: vec![String::from("b")],
+            ports: vec![80, 443],
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config, recurse_options)]
+struct Overrides {
+    connection: Option<Connection>,
+    hosts: Option<Vec<String>>,
+
+    #[merge(strategy = azalia_config::merge::strategy::option::overwrite_some)]
+    level: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Merge)]
+#[merge(crate = azalia_config)]
+struct Connection {
+    url: Option<String>,
+    pool: Option<u32>,
+}
+
+#[test]
+fn option_strategies() {
+    let mut overrides = Overrides {
+        connection: Some(Connection {
+            url: Some(String::from("postgres://localhost")),
+            pool: None,
+        }),
+        hosts: Some(vec![String::from("a")]),
+        level: Some(String::from("info")),
+    };
+
+    let mut report = MergeReport::new();
+    overrides.merge_with_report(
+        Overrides {
+            connection: Some(Connection {
+                url: Some(String::from("postgres://remote")),
+                pool: Some(10),
+            }),
+            hosts: None,
+            level: Some(String::from("debug")),
+        },
+        &mut report,
+    );
+
+    assert_eq!(
+        overrides,
+        Overrides {
+            connection: Some(Connection {
+                url: Some(String::from("postgres://localhost")),
+                pool: Some(10),
+            }),
+            hosts: Some(vec![String::from("a")]),
+            level: Some(String::from("debug")),
+        }
+    );
+
+    assert_eq!(report.changed(), ["connection.pool", "level"]);
+}
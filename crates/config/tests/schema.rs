@@ -0,0 +1,252 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+#![cfg(all(feature = "macros", feature = "schema"))]
+
+use azalia_config::schema::{self, JsonSchema};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// Configuration for a service.
+#[derive(JsonSchema, Default, serde::Serialize)]
+#[schema(crate = azalia_config, default)]
+pub struct Config {
+    /// Port to listen on.
+    port: u16,
+
+    /// Host to listen on.
+    #[schema(default = "0.0.0.0")]
+    host: Option<String>,
+
+    storage: Storage,
+    databases: BTreeMap<String, Database>,
+
+    #[schema(skip)]
+    #[allow(unused)]
+    internal: bool,
+}
+
+#[derive(JsonSchema, Default, serde::Serialize)]
+#[schema(crate = azalia_config, rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Storage {
+    #[default]
+    Filesystem,
+    S3,
+}
+
+#[derive(JsonSchema)]
+#[schema(crate = azalia_config, rename_all = "lowercase")]
+pub enum Auth {
+    /// No authentication.
+    None,
+    Ldap {
+        url: String,
+        base_dn: Option<String>,
+    },
+    Static(String),
+}
+
+#[derive(JsonSchema, serde::Serialize)]
+#[schema(crate = azalia_config, rename_all = "camelCase")]
+pub struct Database {
+    url: String,
+    max_connections: Option<u32>,
+}
+
+#[derive(JsonSchema)]
+#[schema(crate = azalia_config)]
+pub struct Wrapper<T>(T);
+
+#[derive(JsonSchema, serde::Serialize)]
+#[schema(crate = azalia_config)]
+pub struct Node {
+    name: String,
+
+    #[serde(default)]
+    children: Vec<Node>,
+    parent: Option<Box<Node>>,
+    leaf: Option<Leaf>,
+}
+
+#[derive(JsonSchema, serde::Serialize)]
+#[schema(crate = azalia_config)]
+#[serde(default)]
+pub struct Leaf {
+    weight: u32,
+    nodes: Vec<Node>,
+}
+
+impl Default for Leaf {
+    fn default() -> Self {
+        Leaf {
+            weight: 1,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn structs() {
+    let document = schema::document::<Config>();
+
+    assert_eq!(document["$schema"], schema::DRAFT);
+    assert_eq!(document["title"], "Config");
+    assert_eq!(document["description"], "Configuration for a service.");
+    // every field has a default from the container's `Default` implementation
+    assert!(document.get("required").is_none());
+
+    let properties = &document["properties"];
+    assert!(properties.get("internal").is_none());
+    assert_eq!(properties["port"]["description"], "Port to listen on.");
+    assert_eq!(properties["port"]["default"], 0);
+    assert_eq!(properties["host"]["default"], "0.0.0.0");
+    assert_eq!(
+        properties["host"]["anyOf"],
+        json!([{ "type": "string" }, { "type": "null" }])
+    );
+
+    assert_eq!(
+        properties["storage"],
+        json!({ "$ref": "#/$defs/Storage", "default": "filesystem" })
+    );
+
+    assert_eq!(
+        properties["databases"]["additionalProperties"],
+        json!({ "$ref": "#/$defs/Database" })
+    );
+
+    let database = &document["$defs"]["Database"];
+    assert_eq!(database["title"], "Database");
+    assert_eq!(database["required"], json!(["url"]));
+    assert_eq!(database["properties"]["maxConnections"]["anyOf"][0]["type"], "integer");
+}
+
+#[test]
+fn enumerations() {
+    assert_eq!(
+        Storage::json_schema(),
+        json!({
+            "title": "Storage",
+            "type": "string",
+            "enum": ["filesystem", "s3"],
+        })
+    );
+
+    let schema = Auth::json_schema();
+    let variants = schema["oneOf"].as_array().unwrap();
+
+    assert_eq!(variants.len(), 3);
+    assert_eq!(
+        variants[0],
+        json!({ "const": "none", "description": "No authentication." })
+    );
+    assert_eq!(variants[1]["properties"]["ldap"]["required"], json!(["url"]));
+    assert_eq!(variants[2]["properties"]["static"]["type"], "string");
+}
+
+#[test]
+fn generics() {
+    assert_eq!(
+        <Wrapper<bool>>::json_schema(),
+        json!({ "title": "Wrapper", "type": "boolean" })
+    );
+}
+
+#[test]
+fn recursive_types() {
+    let document = schema::document::<Node>();
+
+    assert_eq!(document["title"], "Node");
+    assert_eq!(document["required"], json!(["name"]));
+    assert_eq!(document["properties"]["children"]["items"], json!({ "$ref": "#" }));
+    assert_eq!(
+        document["properties"]["parent"]["anyOf"],
+        json!([{ "$ref": "#" }, { "type": "null" }])
+    );
+
+    assert_eq!(
+        document["properties"]["leaf"]["anyOf"][0],
+        json!({ "$ref": "#/$defs/Leaf" })
+    );
+
+    let leaf = &document["$defs"]["Leaf"];
+    assert!(leaf.get("required").is_none());
+    assert_eq!(leaf["properties"]["nodes"]["items"], json!({ "$ref": "#" }));
+
+    // a type that contains the root type is defined once it isn't the root anymore
+    let document = schema::document::<Leaf>();
+    assert_eq!(
+        document["properties"]["nodes"]["items"],
+        json!({ "$ref": "#/$defs/Node" })
+    );
+
+    assert_eq!(
+        document["$defs"]["Node"]["properties"]["leaf"]["anyOf"][0],
+        json!({ "$ref": "#" })
+    );
+}
+
+#[cfg(feature = "serde")]
+#[derive(JsonSchema, Default, serde::Serialize)]
+#[schema(crate = azalia_config, default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Renamed {
+    max_connections: u32,
+
+    #[serde(rename = "url")]
+    database_url: String,
+
+    #[serde(rename(serialize = "level", deserialize = "logLevel"))]
+    log_level: Option<String>,
+
+    #[schema(rename = "mode")]
+    #[serde(rename = "kind")]
+    backend: Backend,
+}
+
+#[cfg(feature = "serde")]
+#[derive(JsonSchema, Default, serde::Serialize)]
+#[schema(crate = azalia_config)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    #[default]
+    LocalDisk,
+
+    #[serde(rename = "s3")]
+    AmazonS3,
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_renames() {
+    let document = schema::document::<Renamed>();
+    let properties = document["properties"].as_object().unwrap();
+    let mut keys = properties.keys().map(String::as_str).collect::<Vec<_>>();
+    keys.sort_unstable();
+
+    assert_eq!(keys, ["logLevel", "maxConnections", "mode", "url"]);
+
+    // defaults are looked up under the renamed keys of the serialized `Default` value
+    assert_eq!(properties["maxConnections"]["default"], 0);
+    assert_eq!(properties["url"]["default"], "");
+    assert_eq!(document["$defs"]["Backend"]["enum"], json!(["local-disk", "s3"]));
+}
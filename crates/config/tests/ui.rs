@@ -33,3 +33,81 @@ fn tryfromenv() {
     let testcases = trybuild::TestCases::new();
     testcases.compile_fail("./tests/ui/tryfromenv/*.rs");
 }
+
+/// Builds each program in `tests/ui/crate-path` in its own project, where `azalia-config` is
+/// depended on in a different way, without `#[merge(crate = ...)]`.
+///
+/// This is ignored by default since it runs nested `cargo` builds, which take a while
+/// and require network access; run it with `cargo test --features macros --test ui -- --ignored`.
+#[cfg(feature = "macros")]
+#[test]
+#[ignore = "spawns nested cargo builds"]
+fn crate_path() {
+    use std::{fs, path::Path, process::Command};
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("crate-path");
+    let azalia = manifest_dir.join("../azalia");
+
+    let testcases = [
+        (
+            "azalia",
+            format!(
+                "azalia = {{ path = {:?}, features = [\"config\", \"config+macros\"] }}",
+                azalia.display()
+            ),
+        ),
+        (
+            "azalia_config",
+            format!(
+                "azalia-config = {{ path = {:?}, features = [\"macros\"] }}",
+                manifest_dir.display()
+            ),
+        ),
+        (
+            "renamed",
+            format!(
+                "settings = {{ package = \"azalia-config\", path = {:?}, features = [\"macros\"] }}",
+                manifest_dir.display()
+            ),
+        ),
+    ];
+
+    for (name, dependency) in testcases {
+        let project = root.join(name);
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::copy(
+            manifest_dir.join("tests/ui/crate-path").join(format!("{name}.rs")),
+            project.join("src/main.rs"),
+        )
+        .unwrap();
+
+        fs::write(
+            project.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"crate-path-{name}\"\nversion = \"0.0.0\"\nedition = \"2024\"\npublish = false\n\n[workspace]\n\n[dependencies]\n{dependency}\n"
+            ),
+        )
+        .unwrap();
+
+        // reuse the workspace's lockfile so that the dependencies can be resolved offline
+        let lockfile = manifest_dir.join("../../Cargo.lock");
+        if lockfile.exists() {
+            fs::copy(lockfile, project.join("Cargo.lock")).unwrap();
+        }
+
+        let status = Command::new(env!("CARGO"))
+            .arg("run")
+            .arg("--quiet")
+            .arg("--manifest-path")
+            .arg(project.join("Cargo.toml"))
+            .env("CARGO_TARGET_DIR", root.join("target"))
+            .status()
+            .unwrap();
+
+        assert!(
+            status.success(),
+            "`tests/ui/crate-path/{name}.rs` failed to build or run"
+        );
+    }
+}
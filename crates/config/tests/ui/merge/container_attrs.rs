@@ -0,0 +1,42 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use azalia_config::merge::Merge;
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, bound = "T Merge")]
+pub struct Heck1<T> {
+    a: T,
+}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, bound = "", bound = "")]
+pub struct Heck2 {}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, strategy_for = azalia_config::merge::strategy::vec::overwrite)]
+pub struct Heck3 {}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, strategy_for(Vec) = 1234)]
+pub struct Heck4 {}
+
+fn main() {}
@@ -22,11 +22,19 @@
 use azalia_config::merge::Merge;
 
 #[derive(Merge)]
-#[merge(crate = "azalia_config")]
-pub enum Heck1 {}
+#[merge(crate = azalia_config, strategy = 1234)]
+pub enum Heck1 {
+    A,
+}
 
 #[derive(Merge)]
 #[merge(crate = azalia_config)]
-pub enum Heck2 {}
+pub enum Heck2 {
+    A(#[merge(unknown_field)] u32),
+}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, strategy = a, strategy = b)]
+pub enum Heck3 {}
 
 fn main() {}
@@ -57,4 +57,40 @@ pub struct F {
     f: f64,
 }
 
+#[derive(azalia_config::merge::Merge)]
+pub struct G {
+    #[merge(deep, strategy = azalia_config::merge::strategy::maps::btreemap::extend)]
+    g: std::collections::BTreeMap<String, u32>,
+}
+
+#[derive(azalia_config::merge::Merge)]
+pub struct H {
+    #[merge(default = "unknown")]
+    h: bool,
+}
+
+#[derive(azalia_config::merge::Merge)]
+pub struct I {
+    #[merge(default, deep)]
+    i: std::collections::BTreeMap<String, u32>,
+}
+
+#[derive(azalia_config::merge::Merge)]
+pub struct J {
+    #[merge(conflict = "error")]
+    j: String,
+}
+
+#[derive(azalia_config::merge::TryMerge)]
+pub struct K {
+    #[merge(conflict = "warn")]
+    k: String,
+}
+
+#[derive(azalia_config::merge::TryMerge)]
+pub struct L {
+    #[merge(conflict = "error", deep)]
+    l: std::collections::BTreeMap<String, u32>,
+}
+
 fn main() {}
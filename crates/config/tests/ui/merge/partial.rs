@@ -0,0 +1,49 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use azalia_config::merge::{Merge, TryMerge};
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, partial)]
+pub enum A {
+    B,
+}
+
+#[derive(TryMerge)]
+#[merge(crate = azalia_config, partial)]
+pub struct C {
+    c: u32,
+}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config)]
+pub struct D {
+    #[merge(partial)]
+    d: u32,
+}
+
+#[derive(Merge)]
+#[merge(crate = azalia_config, partial(unknown))]
+pub struct E {
+    e: u32,
+}
+
+fn main() {}
@@ -0,0 +1,188 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Throughput of the built-in writers, comparing the writers that format into the
+//! [`WriteLayer`]'s buffer with the functions that go through the adapter for the
+//! allocating `WriteFn` signature, and with the [`baseline`] that every event was
+//! written with before the buffer was introduced.
+
+use azalia_log::{
+    writers::{self, default::Writer, JsonWriter},
+    WriteLayer,
+};
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::{io, sync::RwLock};
+use tracing_subscriber::{layer::SubscriberExt, Layer, Registry};
+
+/// Copy of the old `WriteLayer` and `JsonWriter`: span fields were kept in a
+/// `BTreeMap`, every event was built as a [`serde_json::Value`] tree and serialized
+/// into a fresh `String` that the layer then wrote out.
+mod baseline {
+    use azalia_log::writers::JsonVisitor;
+    use chrono::Local;
+    use serde_json::{json, Map, Value};
+    use std::{collections::BTreeMap, io::Write, process, sync::RwLock};
+    use tracing::{span, Event, Metadata, Subscriber};
+    use tracing_subscriber::{
+        layer::Context,
+        registry::{LookupSpan, SpanRef},
+        Layer,
+    };
+
+    struct Fields(BTreeMap<String, Value>);
+
+    pub struct JsonLayer(pub RwLock<Box<dyn Write + Send + Sync>>);
+
+    impl<S: Subscriber + for<'l> LookupSpan<'l>> Layer<S> for JsonLayer {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut data = BTreeMap::new();
+            attrs.record(&mut JsonVisitor(&mut data));
+
+            span.extensions_mut().insert(Fields(data));
+        }
+
+        fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut exts = span.extensions_mut();
+            values.record(&mut JsonVisitor(&mut exts.get_mut::<Fields>().unwrap().0));
+        }
+
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            let mut spans: Vec<SpanRef<'_, S>> = vec![];
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope.from_root() {
+                    spans.push(span);
+                }
+            }
+
+            let mut buf = json(event, event.metadata(), spans);
+            buf.push('\n');
+
+            let mut writer = self.0.write().unwrap();
+            let _ = writer.write_all(buf.as_bytes());
+        }
+    }
+
+    fn json<S: for<'l> LookupSpan<'l>>(
+        event: &Event<'_>,
+        metadata: &Metadata<'_>,
+        spans: Vec<SpanRef<'_, S>>,
+    ) -> String {
+        let thread = std::thread::current();
+
+        let mut tree = BTreeMap::new();
+        event.record(&mut JsonVisitor(&mut tree));
+
+        let message = tree
+            .remove("message")
+            .unwrap_or(Value::String(String::from("<none provided>")));
+
+        let mut object = Map::new();
+        let fields = match tree.is_empty() {
+            true => Value::Null,
+            false => Value::Object(Map::from_iter(tree)),
+        };
+
+        object.insert("@timestamp".to_owned(), Local::now().to_rfc3339().into());
+        object.insert("message".to_owned(), message);
+        object.insert("metadata.module".to_owned(), metadata.module_path().into());
+        object.insert("metadata.file".to_owned(), metadata.file().into());
+        object.insert("metadata.line".to_owned(), metadata.line().into());
+        object.insert("thread.name".to_owned(), thread.name().unwrap_or("main").into());
+        object.insert("process.id".to_owned(), process::id().into());
+
+        let spans = spans
+            .iter()
+            .map(|span| {
+                let ext = span.extensions();
+                let data = &ext.get::<Fields>().unwrap().0;
+
+                json!({
+                    "fields": match data.is_empty() {
+                        true => None,
+                        false => Some(data)
+                    },
+
+                    "target": span.metadata().target(),
+                    "level": metadata.level().as_str().to_lowercase(),
+                    "name": span.metadata().name(),
+                    "meta": json!({
+                        "module": span.metadata().module_path(),
+                        "file": span.metadata().file(),
+                        "line": span.metadata().line(),
+                    })
+                })
+            })
+            .collect();
+
+        object.insert("spans".to_owned(), Value::Array(spans));
+        object.insert("fields".to_owned(), fields);
+
+        serde_json::to_string(&object).unwrap()
+    }
+}
+
+fn bench<L: Layer<Registry> + Send + Sync + 'static>(c: &mut Criterion, name: &str, layer: L) {
+    let subscriber = tracing_subscriber::registry().with(layer);
+    let mut group = c.benchmark_group("writers");
+    group.throughput(Throughput::Elements(1));
+
+    tracing::subscriber::with_default(subscriber, || {
+        let request = tracing::info_span!("request", method = "GET", path = "/v1/users/@me");
+        let _request = request.enter();
+
+        let db = tracing::info_span!("db", statement = "SELECT * FROM users WHERE id = $1");
+        let _db = db.enter();
+
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                tracing::info!(
+                    user.id = 1234,
+                    took.ms = 1.5,
+                    cached = false,
+                    "fetched \"noel\" from the database"
+                );
+            })
+        });
+    });
+
+    group.finish();
+}
+
+fn writers(c: &mut Criterion) {
+    bench(
+        c,
+        "json (baseline)",
+        baseline::JsonLayer(RwLock::new(Box::new(io::sink()))),
+    );
+    bench(c, "json (adapter)", WriteLayer::new_with(io::sink(), writers::json));
+    bench(c, "JsonWriter", WriteLayer::new_with(io::sink(), JsonWriter::default()));
+    bench(c, "logfmt (adapter)", WriteLayer::new_with(io::sink(), writers::logfmt));
+    bench(
+        c,
+        "default::Writer",
+        WriteLayer::new_with(io::sink(), Writer::default().with_colors(false)),
+    );
+}
+
+criterion_group!(benches, writers);
+criterion_main!(benches);
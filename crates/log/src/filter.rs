@@ -0,0 +1,269 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Level and target filtering from directive strings, like `info,my_crate::db=debug`.
+//!
+//! A directive string is a comma-separated list of directives, where each directive is either:
+//!
+//! * a level (`info`), which is used for all targets that no other directive matched;
+//! * a target and a level (`my_crate::db=debug`), which is used for the target and its children;
+//! * a target without a level (`my_crate`), which enables all levels for the target.
+//!
+//! The levels are `trace`, `debug`, `info`, `warn`, `error` and `off`. When multiple directives
+//! match a target, the most specific target is used. Events without a matching directive and
+//! without a default level are disabled.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    str::FromStr,
+};
+use tracing::{level_filters::LevelFilter, subscriber::Interest, Metadata};
+use tracing_subscriber::layer::{Context, Filter};
+
+/// A list of directives that enable or disable events by their target and level, see the
+/// [module documentation](self) for the syntax.
+///
+/// This can be given to [`WriteLayer::with_directives`](crate::WriteLayer::with_directives),
+/// or used as a per-layer [`Filter`] for any layer with [`Layer::with_filter`].
+///
+/// [`Layer::with_filter`]: tracing_subscriber::Layer::with_filter
+///
+/// ## Example
+/// ```
+/// use azalia_log::filter::Directives;
+/// use tracing::level_filters::LevelFilter;
+///
+/// let directives: Directives = "info,my_crate::db=debug".parse().unwrap();
+/// assert_eq!(directives.max_level(), LevelFilter::DEBUG);
+/// assert_eq!(directives.to_string(), "info,my_crate::db=debug");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directives {
+    default: LevelFilter,
+
+    /// sorted from the most to the least specific target
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Default for Directives {
+    fn default() -> Self {
+        Directives::new(LevelFilter::OFF)
+    }
+}
+
+impl Directives {
+    /// Creates new [`Directives`] that uses `default` for all targets.
+    pub fn new<L: Into<LevelFilter>>(default: L) -> Directives {
+        Directives {
+            default: default.into(),
+            targets: Vec::new(),
+        }
+    }
+
+    /// Uses `level` for `target` and all of its children, replacing the previous level
+    /// for the same target.
+    pub fn with_target<T: Into<String>, L: Into<LevelFilter>>(mut self, target: T, level: L) -> Self {
+        let target = target.into();
+        let level = level.into();
+
+        match self.targets.iter_mut().find(|(existing, _)| *existing == target) {
+            Some((_, existing)) => *existing = level,
+            None => {
+                self.targets.push((target, level));
+                self.targets
+                    .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+            }
+        }
+
+        self
+    }
+
+    /// Returns the level that is used for `target`.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| matches_target(prefix, target))
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    /// Returns `true` if an event or span with the given `metadata` is enabled.
+    pub fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= &self.level_for(metadata.target())
+    }
+
+    /// Returns the most verbose level that any directive enables.
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, LevelFilter::max)
+    }
+
+    /// Parses the directives from the environment variable `key` with [`azalia_config::env`].
+    #[cfg(feature = "config")]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "config")))]
+    pub fn from_env<K: Into<String>>(
+        key: K,
+    ) -> Result<Directives, azalia_config::env::TryParseError<ParseDirectivesError>> {
+        azalia_config::env::try_parse(key)
+    }
+}
+
+/// Returns `true` if `target` is `prefix` or one of its children.
+fn matches_target(prefix: &str, target: &str) -> bool {
+    target
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+fn parse_level(level: &str) -> Option<LevelFilter> {
+    match &*level.to_ascii_lowercase() {
+        "trace" => Some(LevelFilter::TRACE),
+        "debug" => Some(LevelFilter::DEBUG),
+        "info" | "information" => Some(LevelFilter::INFO),
+        "warn" | "warning" => Some(LevelFilter::WARN),
+        "error" => Some(LevelFilter::ERROR),
+        "off" => Some(LevelFilter::OFF),
+        _ => None,
+    }
+}
+
+impl FromStr for Directives {
+    type Err = ParseDirectivesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut directives = Directives::default();
+        for directive in s.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(ParseDirectivesError(directive.to_owned()));
+                    }
+
+                    let level = parse_level(level.trim()).ok_or_else(|| ParseDirectivesError(directive.to_owned()))?;
+                    directives = directives.with_target(target, level);
+                }
+
+                None => match parse_level(directive) {
+                    Some(level) => directives.default = level,
+                    None => directives = directives.with_target(directive, LevelFilter::TRACE),
+                },
+            }
+        }
+
+        Ok(directives)
+    }
+}
+
+/// Formats the directives so that they can be parsed again, i.e, `info,my_crate::db=debug`.
+impl Display for Directives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default.to_string().to_lowercase())?;
+        for (target, level) in self.targets.iter().rev() {
+            write!(f, ",{target}={}", level.to_string().to_lowercase())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "config")]
+#[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "config")))]
+impl azalia_config::env::TryFromEnvValue for Directives {
+    type Error = ParseDirectivesError;
+
+    fn try_from_env_value(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl<S> Filter<S> for Directives {
+    fn enabled(&self, metadata: &Metadata<'_>, _: &Context<'_, S>) -> bool {
+        Directives::enabled(self, metadata)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if Directives::enabled(self, metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(self.max_level())
+    }
+}
+
+/// Error when a directive has an invalid level or an empty target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDirectivesError(String);
+
+impl Display for ParseDirectivesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid directive: '{}'", self.0)
+    }
+}
+
+impl Error for ParseDirectivesError {}
+
+#[cfg(test)]
+mod tests {
+    use super::Directives;
+    use tracing::level_filters::LevelFilter;
+
+    #[test]
+    fn parse() {
+        let directives: Directives = "warn, my_crate=info,my_crate::db=DEBUG,other".parse().unwrap();
+
+        assert_eq!(directives.level_for("hyper"), LevelFilter::WARN);
+        assert_eq!(directives.level_for("my_crate"), LevelFilter::INFO);
+        assert_eq!(directives.level_for("my_crate::http"), LevelFilter::INFO);
+        assert_eq!(directives.level_for("my_crate::db::pool"), LevelFilter::DEBUG);
+        assert_eq!(directives.level_for("my_crate_two"), LevelFilter::WARN);
+        assert_eq!(directives.level_for("other"), LevelFilter::TRACE);
+        assert_eq!(directives.max_level(), LevelFilter::TRACE);
+
+        assert_eq!(
+            directives.to_string(),
+            "warn,other=trace,my_crate=info,my_crate::db=debug"
+        );
+
+        assert_eq!(directives.to_string().parse::<Directives>().unwrap(), directives);
+        assert_eq!("".parse::<Directives>().unwrap().level_for("a"), LevelFilter::OFF);
+
+        assert!("my_crate=loud".parse::<Directives>().is_err());
+        assert!("=info".parse::<Directives>().is_err());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn from_env() {
+        azalia_config::env::enter_with("AZALIA_LOG_DIRECTIVES", "error,my_crate=debug", || {
+            let directives = Directives::from_env("AZALIA_LOG_DIRECTIVES").unwrap();
+            assert_eq!(directives.level_for("my_crate"), LevelFilter::DEBUG);
+            assert_eq!(directives.level_for("hyper"), LevelFilter::ERROR);
+        });
+    }
+}
@@ -37,44 +37,108 @@ mod writers;
 #[cfg(not(feature = "writers"))]
 pub use writers::JsonVisitor;
 
-use std::{io::Write, sync::RwLock};
+pub mod filter;
+pub mod non_blocking;
+
+#[cfg(feature = "rolling")]
+#[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "rolling")))]
+pub mod rolling;
+
+use std::{cell::RefCell, io::Write, sync::RwLock};
 use tracing::{span, Event, Metadata, Subscriber};
 use tracing_subscriber::{
+    filter::Filtered,
     registry::{LookupSpan, SpanRef},
     Layer,
 };
 
-/// Represents a function-based trait to create a [`String`] buffer with pieces you might need. This shouldn't
-/// be implemented directly, but can be written with the following function signature:
+/// Represents a trait that formats an event into the buffer that a [`WriteLayer`] writes.
+///
+/// The buffer is reused for every event that is written on the same thread, and the spans
+/// that the event is in are only looked up if the [`WriteFn`] iterates over them, so an
+/// implementation doesn't need to allocate to format an event. The trailing newline is
+/// written by the [`WriteLayer`].
+///
+/// Functions and closures with the previous, allocating signature still implement
+/// [`WriteFn`] through an adapter that collects the spans and copies the returned [`String`]
+/// into the buffer:
 ///
 /// ```rust,ignore
-/// fn(&tracing::Event, &tracing::Metadata, Vec<serde_json::Value>) -> Result<String, std::fmt::Error>
+/// fn(&tracing::Event, &tracing::Metadata, Vec<SpanRef<'_, S>>) -> String
 /// ```
 pub trait WriteFn<S: for<'l> LookupSpan<'l>>: Send {
-    fn buffer(&self, event: &Event, metadata: &Metadata, spans: Vec<SpanRef<'_, S>>) -> String;
+    /// Formats `event` into `buf`. If this returns an error, the event is not written.
+    fn write_event(
+        &self,
+        event: &Event,
+        metadata: &Metadata,
+        spans: Spans<'_, S>,
+        buf: &mut dyn std::fmt::Write,
+    ) -> std::fmt::Result;
 }
 
-impl<S: for<'l> LookupSpan<'l>, F> WriteFn<S> for F
+impl<S: Subscriber + for<'l> LookupSpan<'l>, F> WriteFn<S> for F
 where
     F: Fn(&Event, &Metadata, Vec<SpanRef<'_, S>>) -> String + Send,
 {
-    fn buffer(&self, event: &Event, metadata: &Metadata, spans: Vec<SpanRef<'_, S>>) -> String {
-        (self)(event, metadata, spans)
+    fn write_event(
+        &self,
+        event: &Event,
+        metadata: &Metadata,
+        spans: Spans<'_, S>,
+        buf: &mut dyn std::fmt::Write,
+    ) -> std::fmt::Result {
+        buf.write_str(&(self)(event, metadata, spans.from_root().collect()))
+    }
+}
+
+/// The spans that an event is in, which are looked up lazily.
+pub struct Spans<'a, S> {
+    ctx: tracing_subscriber::layer::Context<'a, S>,
+    event: &'a Event<'a>,
+}
+
+impl<'a, S: Subscriber + for<'l> LookupSpan<'l>> Spans<'a, S> {
+    /// Returns `true` if the event isn't in any span.
+    pub fn is_empty(&self) -> bool {
+        self.ctx.event_span(self.event).is_none()
+    }
+
+    /// Returns an iterator over the spans, starting from the root span.
+    pub fn from_root(&self) -> impl Iterator<Item = SpanRef<'_, S>> {
+        self.ctx
+            .event_scope(self.event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+    }
+
+    /// Returns an iterator over the spans, starting from the span that the event is in.
+    pub fn leaf_first(&self) -> impl Iterator<Item = SpanRef<'_, S>> {
+        self.ctx.event_scope(self.event).into_iter().flatten()
     }
 }
 
 /// Represents a [`Layer`] for writing to a type that implements [`Write`], with a optional
 /// [`WriteFn`] to go alongside with this type.
 pub struct WriteLayer<S: for<'l> LookupSpan<'l>> {
-    writer: RwLock<Box<dyn Write + Send + Sync>>,
+    writer: Output,
     write_fn: Option<Box<dyn WriteFn<S> + Send + Sync>>,
 }
 
+/// Where a [`WriteLayer`] writes the formatted events to.
+enum Output {
+    /// Writes on the thread that emitted the event.
+    Blocking(RwLock<Box<dyn Write + Send + Sync>>),
+
+    /// Sends to the writer thread of a [`NonBlockingWriter`](non_blocking::NonBlockingWriter).
+    NonBlocking(non_blocking::NonBlockingWriter),
+}
+
 impl<S: for<'l> LookupSpan<'l>> WriteLayer<S> {
     /// Creates a new [`WriteLayer`] without a [`WriteFn`].
     pub fn new<W: Write + Send + Sync + 'static>(writer: W) -> WriteLayer<S> {
         WriteLayer {
-            writer: RwLock::new(Box::new(writer)),
+            writer: Output::Blocking(RwLock::new(Box::new(writer))),
             write_fn: None,
         }
     }
@@ -85,10 +149,42 @@ impl<S: for<'l> LookupSpan<'l>> WriteLayer<S> {
         fn_: F,
     ) -> WriteLayer<S> {
         WriteLayer {
-            writer: RwLock::new(Box::new(writer)),
+            writer: Output::Blocking(RwLock::new(Box::new(writer))),
             write_fn: Some(Box::new(fn_)),
         }
     }
+
+    /// Creates a new [`WriteLayer`] with a specified [`WriteFn`] that writes to `writer` on a
+    /// dedicated thread, see the [`non_blocking`] module for more details.
+    ///
+    /// The returned [`FlushGuard`](non_blocking::FlushGuard) must be held for as long as
+    /// events should be written.
+    pub fn non_blocking<W: Write + Send + 'static, F: WriteFn<S> + Send + Sync + 'static>(
+        writer: W,
+        fn_: F,
+        options: non_blocking::NonBlocking,
+    ) -> (WriteLayer<S>, non_blocking::FlushGuard) {
+        let (writer, guard) = options.spawn(writer);
+        (
+            WriteLayer {
+                writer: Output::NonBlocking(writer),
+                write_fn: Some(Box::new(fn_)),
+            },
+            guard,
+        )
+    }
+
+    /// Only writes events that are enabled by `directives`, callsites that are disabled are
+    /// skipped before the event is created.
+    ///
+    /// The directives are a per-layer filter (see [`Layer::with_filter`]), so other layers in
+    /// the same subscriber still receive the events that this layer doesn't write.
+    pub fn with_directives(self, directives: filter::Directives) -> Filtered<Self, filter::Directives, S>
+    where
+        S: Subscriber,
+    {
+        self.with_filter(directives)
+    }
 }
 
 #[derive(Debug)]
@@ -96,12 +192,19 @@ pub(crate) struct JsonExtension(pub(crate) std::collections::BTreeMap<String, se
 impl<S: Subscriber + for<'l> LookupSpan<'l>> Layer<S> for WriteLayer<S> {
     fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
         let span = ctx.span(id).unwrap();
+        let mut exts = span.extensions_mut();
+
+        // another `WriteLayer` in the same subscriber might've already recorded the fields
+        if exts.get_mut::<JsonExtension>().is_some() {
+            return;
+        }
+
         let mut data = std::collections::BTreeMap::new();
 
         let mut visitor = crate::writers::JsonVisitor(&mut data);
         attrs.record(&mut visitor);
 
-        span.extensions_mut().insert(JsonExtension(data));
+        exts.insert(JsonExtension(data));
     }
 
     fn on_record(&self, span: &span::Id, values: &span::Record<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
@@ -114,7 +217,6 @@ impl<S: Subscriber + for<'l> LookupSpan<'l>> Layer<S> for WriteLayer<S> {
     }
 
     fn on_event(&self, event: &Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
-        let mut writer = self.writer.write().unwrap();
         if let Some(ref fn_) = self.write_fn {
             cfg_if::cfg_if! {
                 if #[cfg(feature = "tracing-log")] {
@@ -127,16 +229,74 @@ impl<S: Subscriber + for<'l> LookupSpan<'l>> Layer<S> for WriteLayer<S> {
                 }
             };
 
-            let mut spans: Vec<SpanRef<'_, S>> = vec![];
-            if let Some(scope) = ctx.event_scope(event) {
-                for span in scope.from_root() {
-                    spans.push(span);
+            BUFFER.with(|buf| match buf.try_borrow_mut() {
+                Ok(mut buf) => {
+                    buf.clear();
+                    self.write_event(fn_.as_ref(), event, metadata, ctx, &mut buf);
+
+                    // don't hold on to the memory of an unusually large event
+                    if buf.capacity() > MAX_RETAINED_BUFFER {
+                        *buf = String::new();
+                    }
                 }
+
+                // the buffer is already in use further up this thread's stack, which tracing
+                // usually prevents by not dispatching events while one is being dispatched
+                Err(_) => self.write_event(fn_.as_ref(), event, metadata, ctx, &mut String::new()),
+            });
+        }
+    }
+}
+
+/// Capacity of a thread's buffer that is kept after an event was written.
+const MAX_RETAINED_BUFFER: usize = 64 * 1024;
+
+thread_local! {
+    static BUFFER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+impl<S: Subscriber + for<'l> LookupSpan<'l>> WriteLayer<S> {
+    fn write_event(
+        &self,
+        fn_: &(dyn WriteFn<S> + Send + Sync),
+        event: &Event<'_>,
+        metadata: &Metadata<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+        buf: &mut String,
+    ) {
+        if let Output::NonBlocking(writer) = &self.writer {
+            let dropped = writer.take_dropped();
+            if dropped > 0 {
+                non_blocking::dropped_event(dropped, |notice| {
+                    let spans = Spans {
+                        ctx: ctx.clone(),
+                        event: notice,
+                    };
+
+                    if fn_.write_event(notice, notice.metadata(), spans, buf).is_ok() {
+                        buf.push('\n');
+                        writer.send_notice(buf.as_bytes().to_vec());
+                    }
+                });
+
+                buf.clear();
             }
+        }
 
-            let buf = fn_.buffer(event, metadata, spans);
-            let _ = write!(writer, "{buf}");
-            let _ = writeln!(writer);
+        if fn_.write_event(event, metadata, Spans { ctx, event }, buf).is_err() {
+            return;
+        }
+
+        // the event is written at once so that a non-blocking writer never splits it
+        buf.push('\n');
+
+        match &self.writer {
+            Output::Blocking(writer) => {
+                let mut writer = writer.write().unwrap();
+                let _ = writer.write_all(buf.as_bytes());
+            }
+
+            Output::NonBlocking(writer) => writer.send(buf.as_bytes().to_vec()),
         }
     }
 }
@@ -144,9 +304,18 @@ impl<S: Subscriber + for<'l> LookupSpan<'l>> Layer<S> for WriteLayer<S> {
 #[cfg(test)]
 mod tests {
     use crate::WriteLayer;
-    use std::io;
-    use tracing::Dispatch;
-    use tracing_subscriber::{layer::SubscriberExt, registry, Layer, Registry};
+    use std::{
+        fmt::Debug,
+        io,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{field::Field, Dispatch, Event, Metadata};
+    use tracing_subscriber::{
+        layer::SubscriberExt,
+        registry,
+        registry::{LookupSpan, SpanRef},
+        Layer, Registry,
+    };
 
     fn __assert_is_layer<S>(_: &dyn Layer<S>) {
         /* no body here */
@@ -170,4 +339,98 @@ mod tests {
         #[cfg(feature = "writers")]
         __assert_is_dispatchable(registry().with(WriteLayer::new_with(io::stdout(), crate::writers::json)));
     }
+
+    /// [`Write`](io::Write) implementation that keeps everything written to it in memory.
+    #[derive(Clone, Default)]
+    pub(crate) struct Capture(Arc<Mutex<Vec<u8>>>);
+    impl Capture {
+        pub(crate) fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn directives() {
+        let capture = Capture::default();
+        let layer = WriteLayer::new_with(
+            capture.clone(),
+            |event: &Event, _: &Metadata, _: Vec<SpanRef<'_, Registry>>| {
+                let mut message = String::new();
+                event.record(&mut |_: &Field, value: &dyn Debug| message = format!("{value:?}"));
+
+                message
+            },
+        )
+        .with_directives("info,azalia_log::tests::db=debug".parse().unwrap());
+
+        tracing::subscriber::with_default(registry().with(layer), || {
+            tracing::debug!("skipped");
+            tracing::info!("written");
+            tracing::debug!(target: "azalia_log::tests::db::pool", "also written");
+            tracing::trace!(target: "azalia_log::tests::db", "also skipped");
+        });
+
+        assert_eq!(capture.contents(), "written\nalso written\n");
+    }
+
+    #[test]
+    fn directives_per_layer() {
+        fn message<S: for<'l> LookupSpan<'l>>(event: &Event, _: &Metadata, _: Vec<SpanRef<'_, S>>) -> String {
+            let mut message = String::new();
+            event.record(&mut |_: &Field, value: &dyn Debug| message = format!("{value:?}"));
+
+            message
+        }
+
+        let (console, file) = (Capture::default(), Capture::default());
+        let subscriber = registry()
+            .with(WriteLayer::new_with(console.clone(), message).with_directives("info".parse().unwrap()))
+            .with(WriteLayer::new_with(file.clone(), message).with_directives("debug".parse().unwrap()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!("skipped");
+            tracing::debug!("only in the file");
+            tracing::info!("in both");
+        });
+
+        assert_eq!(console.contents(), "in both\n");
+        assert_eq!(file.contents(), "only in the file\nin both\n");
+    }
+
+    #[cfg(feature = "writers")]
+    #[test]
+    fn layers_share_span_fields() {
+        let (json, logfmt) = (Capture::default(), Capture::default());
+        let subscriber = registry()
+            .with(WriteLayer::new_with(json.clone(), crate::writers::json).with_directives("debug".parse().unwrap()))
+            .with(
+                WriteLayer::new_with(logfmt.clone(), crate::writers::logfmt).with_directives("info".parse().unwrap()),
+            );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", method = "GET", status = tracing::field::Empty);
+            span.record("status", 200);
+            span.in_scope(|| tracing::info!("hello"));
+        });
+
+        let event: serde_json::Value = serde_json::from_str(json.contents().trim_end()).unwrap();
+        assert_eq!(event["spans"][0]["fields"]["method"], "GET");
+        assert_eq!(event["spans"][0]["fields"]["status"], 200);
+        assert!(
+            logfmt.contents().ends_with(" request.method=GET request.status=200\n"),
+            "{}",
+            logfmt.contents()
+        );
+    }
 }
@@ -0,0 +1,688 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A rolling file appender that implements [`Write`], which can be given to
+//! [`WriteLayer::new_with`](crate::WriteLayer::new_with) to keep log files on disk with
+//! bounded disk usage.
+//!
+//! The active log file is always `<directory>/<file name>`. When it is rotated, it is renamed
+//! to `<file name>.<timestamp>`, where the timestamp is the start of the period that the file
+//! covers, and a new, empty file is created in its place. Files are rotated when the [`Rotation`]
+//! period has passed or when writing an event would exceed the maximum size.
+//!
+//! Rotated files are compressed with gzip (if enabled) and the oldest files are deleted on a
+//! background thread, so that the thread that writes the event isn't blocked by them. If a file
+//! can't be rotated, the event is written to the active file instead. Since these errors can't be
+//! returned from the write that caused them, they are given to the handler from
+//! [`RollingOptions::with_error_handler`].
+//!
+//! Each write is written to a single file, and since [`WriteLayer`](crate::WriteLayer)
+//! writes each event at once, an event is never split between two files.
+//!
+//! ## Example
+//! ```no_run
+//! use azalia_log::{rolling::{Rotation, RollingOptions}, WriteLayer};
+//! use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+//!
+//! let appender = RollingOptions::new("/var/log/my-service", "my-service.log")
+//!     .with_rotation(Rotation::Daily)
+//!     .with_max_size(64 * 1024 * 1024)
+//!     .with_max_files(7)
+//!     .build()
+//!     .unwrap();
+//!
+//! tracing_subscriber::registry()
+//!     .with(WriteLayer::new_with(appender, azalia_log::writers::json))
+//!     .init();
+//! ```
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Timelike};
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+};
+
+const TIMESTAMP_FMT: &str = "%Y-%m-%dT%H-%M-%S";
+
+/// How often the active log file is rotated, regardless of its size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// The file is only rotated when it exceeds the maximum size.
+    #[default]
+    Never,
+
+    /// The file is rotated at the start of every hour.
+    Hourly,
+
+    /// The file is rotated at the start of every day.
+    Daily,
+}
+
+impl Rotation {
+    /// Returns when the period that `time` is in starts, which is `time` itself if the file
+    /// is never rotated by time.
+    fn start(self, time: DateTime<Local>) -> DateTime<Local> {
+        let hour = time
+            .with_minute(0)
+            .and_then(|time| time.with_second(0))
+            .and_then(|time| time.with_nanosecond(0))
+            .unwrap_or(time);
+
+        match self {
+            Rotation::Never => time,
+            Rotation::Hourly => hour,
+            Rotation::Daily => hour.with_hour(0).unwrap_or(hour),
+        }
+    }
+
+    /// Returns when the period that `time` is in ends, or `None` if the file is never
+    /// rotated by time.
+    fn next(self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            Rotation::Never => None,
+            Rotation::Hourly => Some(self.start(time) + Duration::hours(1)),
+            Rotation::Daily => Some(self.start(time) + Duration::days(1)),
+        }
+    }
+}
+
+/// Options for creating a [`RollingFileAppender`].
+#[derive(Debug, Clone)]
+pub struct RollingOptions {
+    /// directory that contains the log files, which is created if it doesn't exist.
+    pub directory: PathBuf,
+
+    /// name of the active log file, rotated files will use this as their prefix.
+    pub file_name: String,
+
+    /// how often the active log file is rotated.
+    pub rotation: Rotation,
+
+    /// maximum size in bytes of the active log file before it is rotated.
+    pub max_size: Option<u64>,
+
+    /// how many rotated files are kept, the oldest files are deleted first.
+    pub max_files: Option<usize>,
+
+    /// whether to compress rotated files with gzip.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "gzip")))]
+    pub compress: bool,
+
+    error_handler: Option<ErrorHandler>,
+}
+
+/// Called with the errors that happen while rotating, compressing or deleting files.
+#[derive(Clone)]
+struct ErrorHandler(Arc<dyn Fn(RollingError) + Send + Sync>);
+
+impl Debug for ErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ErrorHandler(..)")
+    }
+}
+
+impl RollingOptions {
+    /// Creates new [`RollingOptions`] that never rotates `<directory>/<file_name>`.
+    pub fn new<P: Into<PathBuf>, S: Into<String>>(directory: P, file_name: S) -> RollingOptions {
+        RollingOptions {
+            directory: directory.into(),
+            file_name: file_name.into(),
+            rotation: Rotation::default(),
+            max_size: None,
+            max_files: None,
+
+            #[cfg(feature = "gzip")]
+            compress: false,
+            error_handler: None,
+        }
+    }
+
+    /// how often the active log file is rotated.
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// maximum size in bytes of the active log file before it is rotated.
+    pub fn with_max_size(mut self, bytes: u64) -> Self {
+        self.max_size = Some(bytes);
+        self
+    }
+
+    /// how many rotated files are kept, the oldest files are deleted first.
+    pub fn with_max_files(mut self, files: usize) -> Self {
+        self.max_files = Some(files);
+        self
+    }
+
+    /// whether to compress rotated files with gzip.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "gzip")))]
+    pub fn with_compression(mut self, yes: bool) -> Self {
+        self.compress = yes;
+        self
+    }
+
+    /// Calls `handler` with the errors that happen while rotating, compressing or deleting files,
+    /// which are ignored otherwise. The handler is called from the thread that is writing an event
+    /// or from the background thread, so it must not log through the same appender.
+    pub fn with_error_handler<F: Fn(RollingError) + Send + Sync + 'static>(mut self, handler: F) -> Self {
+        self.error_handler = Some(ErrorHandler(Arc::new(handler)));
+        self
+    }
+
+    fn report(&self, error: RollingError) {
+        if let Some(ErrorHandler(ref handler)) = self.error_handler {
+            handler(error);
+        }
+    }
+
+    /// Creates the directory if it doesn't exist and opens the active log file.
+    pub fn build(self) -> io::Result<RollingFileAppender> {
+        RollingFileAppender::open(self, Local::now)
+    }
+}
+
+/// [`Write`] implementation that rotates log files, see the [module documentation](self).
+///
+/// Writes are serialized with a [`Mutex`], so `&RollingFileAppender` also implements
+/// [`Write`] and the same appender can be shared between threads.
+#[derive(Debug)]
+pub struct RollingFileAppender {
+    state: Mutex<State>,
+
+    /// thread that compresses and prunes rotated files, see [`maintain`].
+    worker: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug)]
+struct State {
+    options: RollingOptions,
+    file: File,
+    size: u64,
+
+    /// start of the period that the active file covers, which the rotated file is named after
+    period: DateTime<Local>,
+    next_rotation: Option<DateTime<Local>>,
+    now: fn() -> DateTime<Local>,
+
+    /// sends rotated files to the worker thread, if there is anything to do with them
+    rotated: Option<mpsc::Sender<PathBuf>>,
+}
+
+impl RollingFileAppender {
+    fn open(options: RollingOptions, now: fn() -> DateTime<Local>) -> io::Result<RollingFileAppender> {
+        fs::create_dir_all(&options.directory)?;
+
+        let path = options.directory.join(&options.file_name);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+
+        // a file that is left over from a previous run is rotated once its period has passed
+        let created = match metadata.len() {
+            0 => now(),
+            _ => metadata
+                .modified()
+                .map(DateTime::<Local>::from)
+                .unwrap_or_else(|_| now()),
+        };
+
+        #[cfg(feature = "gzip")]
+        let maintained = options.compress || options.max_files.is_some();
+
+        #[cfg(not(feature = "gzip"))]
+        let maintained = options.max_files.is_some();
+
+        let (rotated, worker) = if maintained {
+            let (sender, receiver) = mpsc::channel();
+            let options = options.clone();
+            let worker = thread::Builder::new()
+                .name(String::from("azalia-log-rolling"))
+                .spawn(move || maintain(&options, receiver))?;
+
+            (Some(sender), Some(worker))
+        } else {
+            (None, None)
+        };
+
+        Ok(RollingFileAppender {
+            state: Mutex::new(State {
+                period: options.rotation.start(created),
+                next_rotation: options.rotation.next(created),
+                size: metadata.len(),
+                options,
+                file,
+                now,
+                rotated,
+            }),
+            worker,
+        })
+    }
+
+    /// Returns the path of the active log file.
+    pub fn path(&self) -> PathBuf {
+        let state = self.lock();
+        state.options.directory.join(&state.options.file_name)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl State {
+    fn write(&mut self, buf: &[u8]) -> io::Result<()> {
+        let now = (self.now)();
+        let expired = self.next_rotation.is_some_and(|next| now >= next);
+        let full = self
+            .options
+            .max_size
+            .is_some_and(|max| self.size > 0 && self.size + buf.len() as u64 > max);
+
+        if expired && self.size == 0 {
+            // there is nothing to rotate, the empty file is used for the new period
+            self.period = self.options.rotation.start(now);
+            self.next_rotation = self.options.rotation.next(now);
+        } else if (expired || full)
+            && let Err(error) = self.rotate(now)
+        {
+            // the event is still written to the active file, rather than being lost
+            self.options.report(RollingError::Rotate(error));
+        }
+
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> io::Result<()> {
+        // a file that can't be rotated is tried again in the next period rather than on every write
+        self.next_rotation = self.options.rotation.next(now);
+        self.file.flush()?;
+
+        let directory = &self.options.directory;
+        let active = directory.join(&self.options.file_name);
+        let stem = format!("{}.{}", self.options.file_name, self.period.format(TIMESTAMP_FMT));
+
+        let mut rotated = directory.join(&stem);
+        let mut counter = 0;
+        while rotated.exists() || rotated.with_extension(gz_extension(&rotated)).exists() {
+            counter += 1;
+            rotated = directory.join(format!("{stem}.{counter}"));
+        }
+
+        // the rename is atomic, so the active file is always either the old or the new one
+        fs::rename(&active, &rotated)?;
+        self.period = self.options.rotation.start(now);
+        self.file = OpenOptions::new().create(true).append(true).open(&active)?;
+        self.size = 0;
+
+        if let Some(ref sender) = self.rotated {
+            let _ = sender.send(rotated);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compresses (if enabled) each rotated file that is received and then deletes the oldest
+/// rotated files, until the [`RollingFileAppender`] is dropped.
+fn maintain(options: &RollingOptions, rotated: mpsc::Receiver<PathBuf>) {
+    for path in rotated {
+        #[cfg(feature = "gzip")]
+        if options.compress
+            && let Err(error) = compress(&path)
+        {
+            options.report(RollingError::Compress { path, error });
+        }
+
+        #[cfg(not(feature = "gzip"))]
+        let _ = path;
+
+        if let Some(max) = options.max_files
+            && let Err(error) = prune(options, max)
+        {
+            options.report(RollingError::Prune(error));
+        }
+    }
+}
+
+/// Deletes the oldest rotated files until there are only `max` left.
+fn prune(options: &RollingOptions, max: usize) -> io::Result<()> {
+    let mut rotated = Vec::new();
+    for entry in fs::read_dir(&options.directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if !name.to_str().is_some_and(|name| is_rotated(&options.file_name, name)) {
+            continue;
+        }
+
+        let modified = entry.metadata()?.modified()?;
+        rotated.push((modified, name, entry.path()));
+    }
+
+    rotated.sort();
+    let excess = rotated.len().saturating_sub(max);
+    for (_, _, path) in rotated.into_iter().take(excess) {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether `name` is a file that was rotated from `file_name`, which is
+/// `<file_name>.<timestamp>`, optionally followed by `.<counter>` and then `.gz`.
+fn is_rotated(file_name: &str, name: &str) -> bool {
+    let Some(rest) = name.strip_prefix(file_name).and_then(|rest| rest.strip_prefix('.')) else {
+        return false;
+    };
+
+    let rest = rest.strip_suffix(".gz").unwrap_or(rest);
+    let (timestamp, counter) = match rest.split_once('.') {
+        Some((timestamp, counter)) => (timestamp, Some(counter)),
+        None => (rest, None),
+    };
+
+    NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FMT).is_ok()
+        && counter.is_none_or(|counter| !counter.is_empty() && counter.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Returns the extension of `path` with `.gz` appended.
+fn gz_extension(path: &Path) -> String {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{ext}.gz"),
+        None => String::from("gz"),
+    }
+}
+
+/// Compresses `path` into `<path>.gz` and removes `path`.
+#[cfg(feature = "gzip")]
+fn compress(path: &Path) -> io::Result<()> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut input = File::open(path)?;
+    let mut encoder = GzEncoder::new(
+        File::create(path.with_extension(gz_extension(path)))?,
+        Compression::default(),
+    );
+
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::remove_file(path)
+}
+
+/// Error that happened while rotating, compressing or deleting files, see
+/// [`RollingOptions::with_error_handler`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RollingError {
+    /// The active file couldn't be rotated, so the event was written to it instead.
+    Rotate(io::Error),
+
+    /// A rotated file couldn't be compressed.
+    #[cfg(feature = "gzip")]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(feature = "gzip")))]
+    Compress { path: PathBuf, error: io::Error },
+
+    /// The oldest rotated files couldn't be deleted.
+    Prune(io::Error),
+}
+
+impl Display for RollingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RollingError::Rotate(error) => write!(f, "failed to rotate the active log file: {error}"),
+
+            #[cfg(feature = "gzip")]
+            RollingError::Compress { path, error } => write!(f, "failed to compress {}: {error}", path.display()),
+            RollingError::Prune(error) => write!(f, "failed to delete old log files: {error}"),
+        }
+    }
+}
+
+impl Error for RollingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RollingError::Rotate(error) | RollingError::Prune(error) => Some(error),
+
+            #[cfg(feature = "gzip")]
+            RollingError::Compress { error, .. } => Some(error),
+        }
+    }
+}
+
+impl Drop for RollingFileAppender {
+    /// Waits until the rotated files were compressed and pruned.
+    fn drop(&mut self) {
+        drop(self.lock().rotated.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Write for RollingFileAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (&*self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush()
+    }
+}
+
+impl Write for &RollingFileAppender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_rotated, RollingError, RollingFileAppender, RollingOptions, Rotation};
+    use chrono::{DateTime, Duration, Local, TimeZone};
+    use std::{
+        fs,
+        io::Write,
+        path::Path,
+        sync::{
+            atomic::{AtomicI64, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    fn files(directory: &Path) -> Vec<String> {
+        let mut files = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn rotates_by_size_and_prunes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let directory = tmp.path().join("nested/logs");
+        let mut appender = RollingOptions::new(&directory, "app.log")
+            .with_max_size(8)
+            .with_max_files(2)
+            .build()
+            .unwrap();
+
+        for event in ["first\n", "second\n", "third\n", "fourth\n"] {
+            appender.write_all(event.as_bytes()).unwrap();
+        }
+
+        // waits until the worker thread pruned the rotated files
+        drop(appender);
+
+        let files = files(&directory);
+        assert_eq!(files.len(), 3, "{files:?}");
+        assert_eq!(fs::read_to_string(directory.join("app.log")).unwrap(), "fourth\n");
+
+        // only the two newest rotated files are kept
+        let rotated = files
+            .iter()
+            .filter(|name| *name != "app.log")
+            .map(|name| fs::read_to_string(directory.join(name)).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(rotated.contains(&String::from("second\n")), "{rotated:?}");
+        assert!(rotated.contains(&String::from("third\n")), "{rotated:?}");
+    }
+
+    #[test]
+    fn rotates_by_time() {
+        static NOW: AtomicI64 = AtomicI64::new(0);
+        fn now() -> DateTime<Local> {
+            Local.timestamp_opt(NOW.load(Ordering::SeqCst), 0).unwrap()
+        }
+
+        let start = Local.with_ymd_and_hms(2100, 1, 1, 0, 30, 0).unwrap();
+        NOW.store(start.timestamp(), Ordering::SeqCst);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let appender = RollingFileAppender::open(
+            RollingOptions::new(tmp.path(), "app.log").with_rotation(Rotation::Hourly),
+            now,
+        )
+        .unwrap();
+
+        (&appender).write_all(b"before\n").unwrap();
+        assert_eq!(files(tmp.path()), ["app.log"]);
+
+        NOW.store((start + Duration::minutes(45)).timestamp(), Ordering::SeqCst);
+        (&appender).write_all(b"after\n").unwrap();
+
+        // the rotated file is named after the hour that it covers
+        assert_eq!(files(tmp.path()), ["app.log", "app.log.2100-01-01T00-00-00"]);
+        assert_eq!(fs::read_to_string(appender.path()).unwrap(), "after\n");
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("app.log.2100-01-01T00-00-00")).unwrap(),
+            "before\n"
+        );
+    }
+
+    #[test]
+    fn writes_when_rotation_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut appender = RollingOptions::new(tmp.path(), "app.log")
+            .with_max_size(4)
+            .with_error_handler({
+                let errors = errors.clone();
+                move |error| errors.lock().unwrap().push(error)
+            })
+            .build()
+            .unwrap();
+
+        appender.write_all(b"first\n").unwrap();
+
+        // the active file can't be renamed since it doesn't exist anymore
+        fs::remove_file(appender.path()).unwrap();
+        appender.write_all(b"second\n").unwrap();
+        appender.flush().unwrap();
+
+        let errors = errors.lock().unwrap();
+        assert!(matches!(errors[..], [RollingError::Rotate(_)]), "{errors:?}");
+    }
+
+    #[test]
+    fn prunes_only_rotated_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        for name in [
+            "app.log.bak",
+            "app.log.json",
+            "app.log.2100-01-01T00-00-00.old",
+            "app.logs",
+        ] {
+            fs::write(tmp.path().join(name), "keep\n").unwrap();
+        }
+
+        let mut appender = RollingOptions::new(tmp.path(), "app.log")
+            .with_max_size(4)
+            .with_max_files(1)
+            .build()
+            .unwrap();
+
+        for event in ["first\n", "second\n", "third\n"] {
+            appender.write_all(event.as_bytes()).unwrap();
+        }
+
+        drop(appender);
+
+        let files = files(tmp.path());
+        assert_eq!(files.len(), 6, "{files:?}");
+        assert!(files.contains(&String::from("app.log.bak")), "{files:?}");
+        assert!(files.contains(&String::from("app.log.json")), "{files:?}");
+
+        assert!(is_rotated("app.log", "app.log.2100-01-01T00-00-00"));
+        assert!(is_rotated("app.log", "app.log.2100-01-01T00-00-00.2"));
+        assert!(is_rotated("app.log", "app.log.2100-01-01T00-00-00.2.gz"));
+        assert!(!is_rotated("app.log", "app.log.2100-01-01T00-00-00.old"));
+        assert!(!is_rotated("app.log", "app.log.gz"));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compresses_rotated_files() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let mut appender = RollingOptions::new(tmp.path(), "app.log")
+            .with_max_size(4)
+            .with_compression(true)
+            .build()
+            .unwrap();
+
+        appender.write_all(b"first\n").unwrap();
+        appender.write_all(b"second\n").unwrap();
+
+        // waits until the worker thread compressed the rotated file
+        drop(appender);
+
+        let files = files(tmp.path());
+        assert_eq!(files.len(), 2, "{files:?}");
+        assert!(files[1].ends_with(".gz"), "{files:?}");
+
+        let mut contents = String::new();
+        GzDecoder::new(fs::File::open(tmp.path().join(&files[1])).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+
+        assert_eq!(contents, "first\n");
+    }
+}
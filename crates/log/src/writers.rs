@@ -31,6 +31,21 @@ mod json;
 #[cfg(feature = "writers")]
 pub use json::*;
 
+#[cfg(feature = "writers")]
+pub mod ecs;
+
+#[cfg(feature = "writers")]
+mod logfmt;
+
+#[cfg(feature = "writers")]
+pub use logfmt::*;
+
+#[cfg(feature = "writers")]
+pub mod otel;
+
+#[cfg(feature = "writers")]
+pub mod syslog;
+
 use serde_json::{json, Value};
 use std::{collections::BTreeMap, fmt::Debug};
 use tracing::field::{Field, Visit};
@@ -67,3 +82,151 @@ impl Visit for JsonVisitor<'_> {
         self.0.insert(field.name().to_string(), json!(format!("{value:?}")));
     }
 }
+
+/// Fields of an event, merged on top of the fields of the spans in its scope with the message
+/// and the first recorded error split out. Used by the schema-shaped JSON writers.
+///
+/// The `log.*` fields that `tracing-log` adds to events from the `log` crate are skipped, since
+/// they are already part of the event's (normalized) metadata.
+#[cfg(feature = "writers")]
+pub(crate) struct Fields {
+    pub(crate) message: Option<Value>,
+    pub(crate) error: Option<String>,
+
+    /// type of the first recorded error, see [`error_type`].
+    pub(crate) error_type: Option<String>,
+    pub(crate) fields: BTreeMap<String, Value>,
+}
+
+#[cfg(feature = "writers")]
+impl Fields {
+    /// Collects the fields of `event`. `spans` are expected to go from the root span, so that fields
+    /// of inner spans take precedence over the outer ones, and the event's fields over all of them.
+    pub(crate) fn collect<S: tracing::Subscriber + for<'l> tracing_subscriber::registry::LookupSpan<'l>>(
+        event: &tracing::Event,
+        spans: &crate::Spans<'_, S>,
+    ) -> Fields {
+        let mut fields = BTreeMap::new();
+        for span in spans.from_root() {
+            let ext = span.extensions();
+            let storage = ext.get::<crate::JsonExtension>().unwrap();
+
+            fields.extend(storage.0.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+
+        let (mut error, mut error_type) = (None, None);
+        event.record(&mut ErrorVisitor {
+            inner: JsonVisitor(&mut fields),
+            error: &mut error,
+            error_type: &mut error_type,
+        });
+
+        fields.retain(|key, _| !key.starts_with("log."));
+        Fields {
+            message: fields.remove("message"),
+            error,
+            error_type,
+            fields,
+        }
+    }
+}
+
+/// Returns the name of the type of `error`. Since it is only known as a trait object, this is
+/// `std::io::Error` or `std::fmt::Error` if it downcasts to either, and otherwise the name that
+/// its [`Debug`] output starts with if it is shaped like a derived one, like `ParseIntError`
+/// for `ParseIntError { kind: InvalidDigit }`.
+///
+/// Errors with a message-style [`Debug`] output, like the ones from `anyhow`, have no type.
+#[cfg(feature = "writers")]
+fn error_type(error: &(dyn std::error::Error + 'static)) -> Option<String> {
+    if error.is::<std::io::Error>() {
+        return Some(String::from("std::io::Error"));
+    }
+
+    if error.is::<std::fmt::Error>() {
+        return Some(String::from("std::fmt::Error"));
+    }
+
+    let debug = format!("{error:?}");
+    let end = debug
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(debug.len());
+
+    let (name, rest) = debug.split_at(end);
+    let derived = rest.starts_with(" {") || rest.starts_with('(');
+
+    (derived && name.starts_with(|c: char| c.is_ascii_uppercase())).then(|| name.to_owned())
+}
+
+#[cfg(feature = "writers")]
+struct ErrorVisitor<'b> {
+    inner: JsonVisitor<'b>,
+    error: &'b mut Option<String>,
+    error_type: &'b mut Option<String>,
+}
+
+#[cfg(feature = "writers")]
+impl Visit for ErrorVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.inner.record_f64(field, value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.inner.record_i64(field, value);
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.inner.record_u64(field, value);
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.inner.record_i128(field, value);
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.inner.record_u128(field, value);
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.inner.record_bool(field, value);
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.inner.record_str(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        match self.error {
+            None => {
+                *self.error = Some(value.to_string());
+                *self.error_type = error_type(value);
+            }
+
+            Some(_) => self.inner.record_error(field, value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.inner.record_debug(field, value);
+    }
+}
+
+/// Adapts the buffer of a [`WriteFn`](crate::WriteFn) to [`io::Write`](std::io::Write), so that
+/// `serde_json` can serialize into it directly.
+#[cfg(feature = "writers")]
+pub(crate) struct FmtWriter<'a>(pub(crate) &'a mut dyn std::fmt::Write);
+
+#[cfg(feature = "writers")]
+impl std::io::Write for FmtWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // `serde_json` only splits its output on character boundaries
+        let s = std::str::from_utf8(buf).map_err(std::io::Error::other)?;
+        self.0.write_str(s).map_err(std::io::Error::other)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
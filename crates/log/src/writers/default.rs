@@ -19,12 +19,15 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::{JsonExtension, WriteFn};
+use crate::{JsonExtension, Spans, WriteFn};
 use chrono::Local;
 use owo_colors::{colors::CustomColor, FgColorDisplay, OwoColorize, Stream};
-use std::{borrow::Cow, fmt::Write};
-use tracing::{Event, Level, Metadata};
-use tracing_subscriber::registry::{LookupSpan, SpanRef};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
 
 mod visitor;
 
@@ -124,52 +127,52 @@ impl Writer {
     }
 }
 
-impl<L: for<'a> LookupSpan<'a>> WriteFn<L> for Writer {
-    fn buffer(&self, event: &Event, metadata: &Metadata, spans: Vec<SpanRef<'_, L>>) -> String {
-        self.write(event, metadata, spans)
+impl<L: Subscriber + for<'a> LookupSpan<'a>> WriteFn<L> for Writer {
+    fn write_event(&self, event: &Event, metadata: &Metadata, spans: Spans<'_, L>, buf: &mut dyn Write) -> fmt::Result {
+        self.write(event, metadata, &spans, buf)
     }
 }
 
 impl Writer {
-    pub(crate) fn write<L: for<'a> LookupSpan<'a>>(
+    pub(crate) fn write<L: Subscriber + for<'a> LookupSpan<'a>, W: Write + ?Sized>(
         &self,
         event: &Event<'_>,
         metadata: &Metadata<'_>,
-        spans: Vec<SpanRef<'_, L>>,
-    ) -> String {
-        let mut buf = String::new();
+        spans: &Spans<'_, L>,
+        buf: &mut W,
+    ) -> fmt::Result {
         if self.print_timestamp {
-            self.write_timestamp(&mut buf);
+            self.write_timestamp(buf);
             let _ = write!(buf, " ");
         }
 
         if self.print_level {
-            self.write_level(metadata, &mut buf);
+            self.write_level(metadata, buf);
             let _ = write!(buf, " ");
         }
 
         if self.print_module || self.print_thread {
-            self.print_metadata(metadata, &mut buf);
+            self.print_metadata(metadata, buf);
             let _ = write!(buf, " ");
         }
 
         if self.emit_spans && !spans.is_empty() {
-            self.print_spans(&spans, &mut buf);
+            self.print_spans(spans, buf);
         }
 
         let mut visitor = visitor::Visitor {
             result: Ok(()),
-            writer: &mut buf,
+            writer: buf,
             colors: self.colors,
             stream: self.stream,
         };
 
         event.record(&mut visitor);
 
-        buf
+        visitor.result
     }
 
-    pub(crate) fn write_timestamp<W: Write>(&self, buf: &mut W) {
+    pub(crate) fn write_timestamp<W: Write + ?Sized>(&self, buf: &mut W) {
         let now = Local::now().format(&self.timestamp_fmt);
         if self.colors {
             let _ = write!(
@@ -184,7 +187,7 @@ impl Writer {
         let _ = write!(buf, "{now}");
     }
 
-    pub(crate) fn write_level<W: Write>(&self, metadata: &Metadata<'_>, buf: &mut W) {
+    pub(crate) fn write_level<W: Write + ?Sized>(&self, metadata: &Metadata<'_>, buf: &mut W) {
         if self.colors {
             let level = metadata.level();
             let level = match *level {
@@ -221,7 +224,7 @@ impl Writer {
         let _ = write!(buf, "{}", metadata.level());
     }
 
-    pub(crate) fn print_metadata<W: Write>(&self, metadata: &Metadata<'_>, buf: &mut W) {
+    pub(crate) fn print_metadata<W: Write + ?Sized>(&self, metadata: &Metadata<'_>, buf: &mut W) {
         let module = metadata.module_path().unwrap_or("unknown");
         let thread = std::thread::current();
         let name = thread.name().unwrap_or("main");
@@ -265,8 +268,12 @@ impl Writer {
         }
     }
 
-    pub(crate) fn print_spans<L: for<'a> LookupSpan<'a>, W: Write>(&self, spans: &[SpanRef<'_, L>], buf: &mut W) {
-        for span in spans {
+    pub(crate) fn print_spans<L: Subscriber + for<'a> LookupSpan<'a>, W: Write + ?Sized>(
+        &self,
+        spans: &Spans<'_, L>,
+        buf: &mut W,
+    ) {
+        for span in spans.from_root() {
             if self.colors {
                 let _ = write!(buf, "{}", "{".if_supports_color(self.stream, |txt| txt.bold()));
             } else {
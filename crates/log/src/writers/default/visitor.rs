@@ -23,14 +23,14 @@ use owo_colors::{OwoColorize, Stream};
 use std::fmt;
 use tracing::field::Visit;
 
-pub struct Visitor<'s, W: fmt::Write + Send> {
+pub struct Visitor<'s, W: fmt::Write + ?Sized> {
     pub result: fmt::Result,
     pub writer: &'s mut W,
     pub stream: Stream,
     pub colors: bool,
 }
 
-impl<W: fmt::Write + Send> Visit for Visitor<'_, W> {
+impl<W: fmt::Write + ?Sized> Visit for Visitor<'_, W> {
     fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn fmt::Debug) {
         // Don't even do anything if the `result` was poisoned by an internal formatting error
         if self.result.is_err() {
@@ -47,16 +47,17 @@ impl<W: fmt::Write + Send> Visit for Visitor<'_, W> {
                 self.result = write!(self.writer, "{value:?}");
             }
 
-            name => {
-                let value = match self.colors {
-                    true => format!("{name}={value:?}")
-                        .if_supports_color(self.stream, |x| x.fg_rgb::<134, 134, 134>())
-                        .to_string(),
-
-                    false => format!("{name}={value:?}"),
-                };
+            name if self.colors => {
+                let value = format!("{name}={value:?}");
+                self.result = write!(
+                    self.writer,
+                    " {}",
+                    value.if_supports_color(self.stream, |x| x.fg_rgb::<134, 134, 134>())
+                );
+            }
 
-                self.result = write!(self.writer, " {value}");
+            name => {
+                self.result = write!(self.writer, " {name}={value:?}");
             }
         }
     }
@@ -0,0 +1,232 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! [`WriteFn`] that emits JSON documents following the [Elastic Common Schema].
+//!
+//! Every document contains `@timestamp`, `log.level`, `log.logger`, `message`, `ecs.version`,
+//! `process.pid`, `process.thread.name` and `log.origin.file.*`. The first error that was
+//! recorded on the event is written as `error.message` and `error.type` (if the type can be told
+//! from the error), and the remaining fields
+//! of the event and its spans are written to the root of the document, next to the
+//! [resource attributes].
+//!
+//! [Elastic Common Schema]: https://www.elastic.co/guide/en/ecs/current/index.html
+//! [resource attributes]: Writer::with_resource
+
+use crate::{
+    writers::{Fields, FmtWriter},
+    Spans, WriteFn,
+};
+use chrono::{SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, fmt, process};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Version of the Elastic Common Schema that documents conform to.
+pub const ECS_VERSION: &str = "8.11.0";
+
+/// Writer that emits Elastic Common Schema documents, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct Writer {
+    /// Attributes of the service that is emitting the events, like `service.name`. These are written
+    /// to the root of every document.
+    pub resource: BTreeMap<String, Value>,
+}
+
+impl Writer {
+    /// Sets the `service.name` attribute.
+    pub fn with_service_name<S: Into<String>>(self, name: S) -> Self {
+        self.with_resource("service.name", name.into())
+    }
+
+    /// Sets an attribute of the service that is emitting the events, like `service.version`
+    /// or `deployment.environment`.
+    pub fn with_resource<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.resource.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl<L: Subscriber + for<'a> LookupSpan<'a>> WriteFn<L> for Writer {
+    fn write_event(
+        &self,
+        event: &Event,
+        metadata: &Metadata,
+        spans: Spans<'_, L>,
+        buf: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        serde_json::to_writer(FmtWriter(buf), &self.document(event, metadata, &spans)).map_err(|_| fmt::Error)
+    }
+}
+
+impl Writer {
+    /// Builds the document as a [`Map`] rather than streaming it like
+    /// [`JsonWriter`](super::JsonWriter): span fields, event fields, resource attributes
+    /// and the ECS fields all share the root object, and a key that more than one of them
+    /// sets must be written once with the last one winning, which needs every key up front.
+    fn document<L: Subscriber + for<'a> LookupSpan<'a>>(
+        &self,
+        event: &Event<'_>,
+        metadata: &Metadata<'_>,
+        spans: &Spans<'_, L>,
+    ) -> Map<String, Value> {
+        let thread = std::thread::current();
+        let Fields {
+            message,
+            error,
+            error_type,
+            fields,
+        } = Fields::collect(event, spans);
+
+        // fields can't overwrite the resource attributes, and neither can override the
+        // fields that are defined by ECS
+        let mut doc = Map::from_iter(fields);
+        doc.extend(self.resource.iter().map(|(key, value)| (key.clone(), value.clone())));
+
+        doc.insert(
+            "@timestamp".into(),
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true).into(),
+        );
+
+        doc.insert("ecs.version".into(), ECS_VERSION.into());
+        doc.insert("log.level".into(), metadata.level().as_str().to_lowercase().into());
+        doc.insert("log.logger".into(), metadata.target().into());
+        doc.insert("process.pid".into(), process::id().into());
+        doc.insert("process.thread.name".into(), thread.name().unwrap_or("main").into());
+
+        if let Some(file) = metadata.file() {
+            doc.insert("log.origin.file.name".into(), file.into());
+        }
+
+        if let Some(line) = metadata.line() {
+            doc.insert("log.origin.file.line".into(), line.into());
+        }
+
+        if let Some(message) = message {
+            doc.insert("message".into(), message);
+        }
+
+        if let Some(error) = error {
+            doc.insert("error.message".into(), error.into());
+        }
+
+        if let Some(error_type) = error_type {
+            doc.insert("error.type".into(), error_type.into());
+        }
+
+        doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Writer, ECS_VERSION};
+    use crate::{tests::Capture, WriteLayer};
+    use serde_json::{json, Value};
+    use std::io;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn document() {
+        let capture = Capture::default();
+        let writer = Writer::default()
+            .with_service_name("azalia")
+            .with_resource("deployment.environment", "test");
+
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), writer)),
+            || {
+                let err = io::Error::other("disk is on fire");
+                tracing::info_span!("request", method = "GET", user = "outer").in_scope(|| {
+                    tracing::error!(
+                        target: "app",
+                        error = &err as &dyn std::error::Error,
+                        user = "noel",
+                        log.target = "leaked",
+                        "oh no"
+                    );
+                });
+            },
+        );
+
+        let doc: Value = serde_json::from_str(capture.contents().trim_end()).unwrap();
+        assert!(doc["@timestamp"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(doc["ecs.version"], ECS_VERSION);
+        assert_eq!(doc["log.level"], "error");
+        assert_eq!(doc["log.logger"], "app");
+        assert_eq!(doc["message"], "oh no");
+        assert_eq!(doc["error.message"], "disk is on fire");
+        assert_eq!(doc["error.type"], "std::io::Error");
+        assert_eq!(doc["service.name"], "azalia");
+        assert_eq!(doc["deployment.environment"], "test");
+        assert_eq!(doc["method"], "GET");
+        assert_eq!(doc["user"], "noel");
+        assert_eq!(doc["process.pid"], json!(std::process::id()));
+        assert!(doc.get("error").is_none());
+        assert!(doc.get("log.target").is_none());
+    }
+
+    #[test]
+    fn error_type() {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), Writer::default())),
+            || {
+                let err = "x".parse::<u8>().unwrap_err();
+                tracing::error!(error = &err as &dyn std::error::Error, "invalid port");
+            },
+        );
+
+        let doc: Value = serde_json::from_str(capture.contents().trim_end()).unwrap();
+        assert_eq!(doc["error.message"], "invalid digit found in string");
+        assert_eq!(doc["error.type"], "ParseIntError");
+    }
+
+    #[test]
+    fn message_style_error() {
+        struct Report;
+
+        impl std::fmt::Debug for Report {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Failed to connect to the database\n\nCaused by:\n    connection refused")
+            }
+        }
+
+        impl std::fmt::Display for Report {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Failed to connect to the database")
+            }
+        }
+
+        impl std::error::Error for Report {}
+
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), Writer::default())),
+            || tracing::error!(error = &Report as &dyn std::error::Error, "startup failed"),
+        );
+
+        let doc: Value = serde_json::from_str(capture.contents().trim_end()).unwrap();
+        assert_eq!(doc["error.message"], "Failed to connect to the database");
+        assert!(doc.get("error.type").is_none());
+    }
+}
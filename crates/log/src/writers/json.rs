@@ -19,65 +19,613 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use super::JsonVisitor;
-use crate::JsonExtension;
-use chrono::Local;
-use serde_json::{json, Value};
-use std::{collections::BTreeMap, process};
-use tracing::{Event, Metadata};
+use super::FmtWriter;
+use crate::{JsonExtension, Spans, WriteFn};
+use chrono::{
+    format::{Item, StrftimeItems},
+    Local, Utc,
+};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+use serde_json::{ser::PrettyFormatter, Map, Value};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Debug},
+    process,
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Metadata, Subscriber,
+};
 use tracing_subscriber::registry::{LookupSpan, SpanRef};
 
 /// Provides a Logstash-style [`WriteFn`](crate::WriteFn) implementation as a stringified JSON object.
+///
+/// This formats events like [`JsonWriter::default()`], but goes through the adapter for the
+/// allocating [`WriteFn`](crate::WriteFn) signature; use [`JsonWriter`] to write events into
+/// the buffer of the [`WriteLayer`](crate::WriteLayer) directly.
 pub fn json<S: for<'l> LookupSpan<'l>>(event: &Event, metadata: &Metadata, spans: Vec<SpanRef<'_, S>>) -> String {
-    let now = Local::now();
-    let thread = std::thread::current();
-    let pid = process::id();
-
-    let mut tree = BTreeMap::new();
-    let mut visitor = JsonVisitor(&mut tree);
-    event.record(&mut visitor);
-
-    let message = tree
-        .remove("message")
-        .unwrap_or(Value::String(String::from("<none provided>")));
-
-    let mut spans_as_json: Vec<Value> = vec![];
-    for span in spans.iter() {
-        let ext = span.extensions();
-        let storage = ext.get::<JsonExtension>().unwrap();
-        let data = &storage.0;
-
-        spans_as_json.push(json!({
-            // show `null` if there are no fields available
-            "fields": match data.is_empty() {
-                true => None,
-                false => Some(data)
-            },
+    let mut buf = String::new();
+    let _ = JsonWriter::default().write(event, metadata, spans.as_slice(), &mut buf);
+
+    buf
+}
+
+/// Timezone that a [`JsonWriter`] writes timestamps in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timezone {
+    /// The system's local timezone.
+    #[default]
+    Local,
+
+    /// Coordinated Universal Time.
+    Utc,
+}
+
+/// Configurable version of the [`json()`] writer, which emits Logstash-style JSON objects:
+///
+/// ```json
+/// {
+///   "@timestamp": "2025-01-01T00:00:00.000000+00:00",
+///   "message": "hello world",
+///   "metadata.module": "my_crate",
+///   "metadata.file": "src/main.rs",
+///   "metadata.line": 3,
+///   "thread.name": "main",
+///   "process.id": 1,
+///   "spans": [],
+///   "fields": null
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonWriter {
+    /// [`chrono` format](chrono::format::strftime) for timestamps, RFC 3339 is used if this is `None`
+    /// or isn't a valid format.
+    pub timestamp_fmt: Option<Cow<'static, str>>,
+
+    /// timezone that timestamps are written in.
+    pub timezone: Timezone,
+
+    /// whether to write the event's fields in the root object rather than in `fields`. Fields
+    /// never replace the keys that the writer emits itself.
+    pub flatten_fields: bool,
+
+    /// whether to emit the spans that the event is in.
+    pub emit_spans: bool,
+
+    /// keys that should be written under a different name, like `thread.name` to `thread`.
+    pub keys: BTreeMap<Cow<'static, str>, Cow<'static, str>>,
+
+    /// static fields that are written in the root object of every event.
+    pub extra_fields: Map<String, Value>,
+
+    /// whether to pretty-print the JSON object. This is useful in development, but most log
+    /// collectors expect one object per line.
+    pub pretty: bool,
+}
+
+impl Default for JsonWriter {
+    fn default() -> Self {
+        Self {
+            timestamp_fmt: None,
+            timezone: Timezone::default(),
+            flatten_fields: false,
+            emit_spans: true,
+            keys: BTreeMap::new(),
+            extra_fields: Map::new(),
+            pretty: false,
+        }
+    }
+}
+
+impl JsonWriter {
+    /// Sets the [`chrono` format](chrono::format::strftime) for timestamps. Timestamps are written
+    /// in RFC 3339 if `fmt` has an unknown specifier, like `%Q`.
+    pub fn with_timestamp_fmt<S: Into<Cow<'static, str>>>(mut self, fmt: S) -> Self {
+        self.timestamp_fmt = Some(fmt.into());
+        self
+    }
+
+    /// Sets the [`Timezone`] that timestamps are written in.
+    pub fn with_timezone(mut self, timezone: Timezone) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// whether to write the event's fields in the root object rather than in `fields`.
+    pub fn flatten_fields(mut self, yes: bool) -> Self {
+        self.flatten_fields = yes;
+        self
+    }
+
+    /// whether to emit the spans that the event is in.
+    pub fn emit_spans(mut self, yes: bool) -> Self {
+        self.emit_spans = yes;
+        self
+    }
+
+    /// Writes the `key` that the writer emits, like `@timestamp` or `fields`, as `name` instead.
+    pub fn rename_key<K: Into<Cow<'static, str>>, N: Into<Cow<'static, str>>>(mut self, key: K, name: N) -> Self {
+        self.keys.insert(key.into(), name.into());
+        self
+    }
+
+    /// Adds a static field that is written in the root object of every event.
+    pub fn with_field<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.extra_fields.insert(key.into(), value.into());
+        self
+    }
+
+    /// whether to pretty-print the JSON object.
+    pub fn pretty(mut self, yes: bool) -> Self {
+        self.pretty = yes;
+        self
+    }
+}
+
+impl<L: Subscriber + for<'a> LookupSpan<'a>> WriteFn<L> for JsonWriter {
+    fn write_event(
+        &self,
+        event: &Event,
+        metadata: &Metadata,
+        spans: Spans<'_, L>,
+        buf: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        self.write(event, metadata, &spans, buf)
+    }
+}
+
+/// Keys that the writer emits itself.
+const KEYS: [&str; 9] = [
+    "@timestamp",
+    "message",
+    "metadata.module",
+    "metadata.file",
+    "metadata.line",
+    "thread.name",
+    "process.id",
+    "spans",
+    "fields",
+];
+
+impl JsonWriter {
+    /// Streams the JSON object of `event` into `buf`, without building it in memory first.
+    pub(crate) fn write<W: SpanSource + ?Sized>(
+        &self,
+        event: &Event<'_>,
+        metadata: &Metadata<'_>,
+        spans: &W,
+        buf: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        let document = Document {
+            writer: self,
+            event,
+            metadata,
+            spans,
+        };
+
+        match self.pretty {
+            true => document.serialize(&mut serde_json::Serializer::with_formatter(
+                FmtWriter(buf),
+                PrettyFormatter::new(),
+            )),
+
+            false => document.serialize(&mut serde_json::Serializer::new(FmtWriter(buf))),
+        }
+        .map_err(|_| fmt::Error)
+    }
+
+    fn key<'s>(&'s self, key: &'static str) -> &'s str {
+        self.keys.get(key).map(|name| name.as_ref()).unwrap_or(key)
+    }
+
+    fn is_reserved(&self, name: &str) -> bool {
+        KEYS.iter().any(|key| self.key(key) == name)
+    }
+}
+
+/// Spans that a [`JsonWriter`] can emit, which are either looked up lazily or were
+/// collected by the adapter for the allocating [`WriteFn`] signature.
+pub(crate) trait SpanSource {
+    type Subscriber: for<'l> LookupSpan<'l>;
+
+    fn try_for_each<E>(&self, f: impl FnMut(&SpanRef<'_, Self::Subscriber>) -> Result<(), E>) -> Result<(), E>;
+}
+
+impl<S: for<'l> LookupSpan<'l>> SpanSource for [SpanRef<'_, S>] {
+    type Subscriber = S;
+
+    fn try_for_each<E>(&self, f: impl FnMut(&SpanRef<'_, S>) -> Result<(), E>) -> Result<(), E> {
+        self.iter().try_for_each(f)
+    }
+}
+
+impl<S: Subscriber + for<'l> LookupSpan<'l>> SpanSource for Spans<'_, S> {
+    type Subscriber = S;
+
+    fn try_for_each<E>(&self, mut f: impl FnMut(&SpanRef<'_, S>) -> Result<(), E>) -> Result<(), E> {
+        self.from_root().try_for_each(|span| f(&span))
+    }
+}
+
+struct Document<'a, W: ?Sized> {
+    writer: &'a JsonWriter,
+    event: &'a Event<'a>,
+    metadata: &'a Metadata<'a>,
+    spans: &'a W,
+}
+
+impl<W: SpanSource + ?Sized> Serialize for Document<'_, W> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let writer = self.writer;
+        let thread = std::thread::current();
+
+        let mut count = Count::default();
+        self.event.record(&mut count);
+
+        let mut map = serializer.serialize_map(None)?;
+
+        // keys are written once, so a static field is skipped if the event or
+        // the writer itself emits the same key
+        for (key, value) in &writer.extra_fields {
+            let replaced = writer.flatten_fields && self.event.metadata().fields().field(key).is_some();
+            if !replaced && !writer.is_reserved(key) {
+                map.serialize_entry(key, value)?;
+            }
+        }
+
+        if writer.flatten_fields {
+            record_entries(self.event, &mut map, |name| {
+                (name != "message" && !writer.is_reserved(name)).then_some(name)
+            })?;
+        }
+
+        map.serialize_entry(writer.key("@timestamp"), &Timestamp(writer))?;
+        match count.message {
+            true => record_entries(self.event, &mut map, |name| {
+                (name == "message").then(|| writer.key("message"))
+            })?,
+
+            false => map.serialize_entry(writer.key("message"), "<none provided>")?,
+        }
+
+        map.serialize_entry(writer.key("metadata.module"), &self.metadata.module_path())?;
+        map.serialize_entry(writer.key("metadata.file"), &self.metadata.file())?;
+        map.serialize_entry(writer.key("metadata.line"), &self.metadata.line())?;
+        map.serialize_entry(writer.key("thread.name"), thread.name().unwrap_or("main"))?;
+        map.serialize_entry(writer.key("process.id"), &process::id())?;
+
+        if writer.emit_spans {
+            map.serialize_entry(
+                writer.key("spans"),
+                &SpanList {
+                    spans: self.spans,
+                    level: self.metadata.level(),
+                },
+            )?;
+        }
+
+        if !writer.flatten_fields {
+            map.serialize_entry(
+                writer.key("fields"),
+                &EventFields {
+                    event: self.event,
+                    empty: count.fields == 0,
+                },
+            )?;
+        }
+
+        map.end()
+    }
+}
+
+struct Timestamp<'a>(&'a JsonWriter);
+impl Serialize for Timestamp<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // `%+` is the same format as `DateTime::to_rfc3339`. chrono fails to format the whole
+        // timestamp on an invalid specifier, which would drop every event
+        let fmt = match self.0.timestamp_fmt.as_deref() {
+            Some(fmt) if !StrftimeItems::new(fmt).any(|item| item == Item::Error) => fmt,
+            _ => "%+",
+        };
 
-            "target": span.metadata().target(),
-            "level": metadata.level().as_str().to_lowercase(),
-            "name": span.metadata().name(),
-            "meta": json!({
-                "module": span.metadata().module_path(),
-                "file": span.metadata().file(),
-                "line": span.metadata().line(),
+        match self.0.timezone {
+            Timezone::Local => serializer.collect_str(&Local::now().format(fmt)),
+            Timezone::Utc => serializer.collect_str(&Utc::now().format(fmt)),
+        }
+    }
+}
+
+struct SpanList<'a, W: ?Sized> {
+    spans: &'a W,
+    level: &'a Level,
+}
+
+impl<W: SpanSource + ?Sized> Serialize for SpanList<'_, W> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(None)?;
+        self.spans.try_for_each(|span| {
+            seq.serialize_element(&SpanObject {
+                span,
+                level: self.level,
             })
-        }));
-    }
-
-    serde_json::to_string(&json!({
-        "@timestamp": now.to_rfc3339(),
-        "message": message,
-        "metadata.module": metadata.module_path(),
-        "metadata.file": metadata.file(),
-        "metadata.line": metadata.line(),
-        "thread.name": thread.name().unwrap_or("main"),
-        "process.id": pid,
-        "spans": spans_as_json,
-        "fields": match tree.is_empty() {
-            true => None,
-            false => Some(tree),
+        })?;
+
+        seq.end()
+    }
+}
+
+struct SpanObject<'a, 'b, L: for<'l> LookupSpan<'l>> {
+    span: &'a SpanRef<'b, L>,
+    level: &'a Level,
+}
+
+impl<L: for<'l> LookupSpan<'l>> Serialize for SpanObject<'_, '_, L> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ext = self.span.extensions();
+        let data = &ext.get::<JsonExtension>().unwrap().0;
+        let metadata = self.span.metadata();
+
+        let mut map = serializer.serialize_map(Some(5))?;
+
+        // show `null` if there are no fields available
+        map.serialize_entry("fields", &(!data.is_empty()).then_some(data))?;
+        map.serialize_entry("target", metadata.target())?;
+        map.serialize_entry("level", lowercase(self.level))?;
+        map.serialize_entry("name", metadata.name())?;
+        map.serialize_entry("meta", &SpanMeta(metadata))?;
+        map.end()
+    }
+}
+
+struct SpanMeta<'a>(&'a Metadata<'a>);
+impl Serialize for SpanMeta<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(3))?;
+        map.serialize_entry("module", &self.0.module_path())?;
+        map.serialize_entry("file", &self.0.file())?;
+        map.serialize_entry("line", &self.0.line())?;
+        map.end()
+    }
+}
+
+struct EventFields<'a> {
+    event: &'a Event<'a>,
+    empty: bool,
+}
+
+impl Serialize for EventFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.empty {
+            return serializer.serialize_none();
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+        record_entries(self.event, &mut map, |name| (name != "message").then_some(name))?;
+        map.end()
+    }
+}
+
+fn lowercase(level: &Level) -> &'static str {
+    match *level {
+        Level::TRACE => "trace",
+        Level::DEBUG => "debug",
+        Level::INFO => "info",
+        Level::WARN => "warn",
+        Level::ERROR => "error",
+    }
+}
+
+#[derive(Default)]
+struct Count {
+    message: bool,
+    fields: usize,
+}
+
+impl Visit for Count {
+    fn record_debug(&mut self, field: &Field, _: &dyn Debug) {
+        match field.name() {
+            "message" => self.message = true,
+            _ => self.fields += 1,
         }
-    }))
-    .unwrap()
+    }
+}
+
+/// Serializes the fields of `event` into `map`, under the key that `key` returns for
+/// the name of the field. Fields are skipped if `key` returns `None`.
+fn record_entries<'k, M: SerializeMap>(
+    event: &Event<'_>,
+    map: &mut M,
+    key: impl FnMut(&'static str) -> Option<&'k str>,
+) -> Result<(), M::Error> {
+    let mut entries = Entries {
+        map,
+        key,
+        result: Ok(()),
+    };
+
+    event.record(&mut entries);
+    entries.result
+}
+
+struct Entries<'m, M: SerializeMap, F> {
+    map: &'m mut M,
+    key: F,
+    result: Result<(), M::Error>,
+}
+
+impl<'k, M: SerializeMap, F: FnMut(&'static str) -> Option<&'k str>> Entries<'_, M, F> {
+    fn entry(&mut self, field: &Field, value: FieldValue<'_>) {
+        if self.result.is_err() {
+            return;
+        }
+
+        if let Some(key) = (self.key)(field.name()) {
+            self.result = self.map.serialize_entry(key, &value);
+        }
+    }
+}
+
+macro_rules! impl_entries_instructions {
+    ($($name:ident => $ty:ty as $variant:ident),*) => {
+        $(
+            fn $name(&mut self, field: &Field, value: $ty) {
+                self.entry(field, FieldValue::$variant(value));
+            }
+        )*
+    }
+}
+
+impl<'k, M: SerializeMap, F: FnMut(&'static str) -> Option<&'k str>> Visit for Entries<'_, M, F> {
+    impl_entries_instructions! {
+        record_f64 => f64 as F64,
+        record_i64 => i64 as I64,
+        record_u64 => u64 as U64,
+        record_i128 => i128 as I128,
+        record_u128 => u128 as U128,
+        record_bool => bool as Bool,
+        record_str => &str as Str,
+        record_error => &(dyn Error + 'static) as Error,
+        record_debug => &dyn Debug as Debug
+    }
+}
+
+/// A value that was recorded on an event, serialized the same way as the [`JsonVisitor`](super::JsonVisitor)
+/// records it.
+enum FieldValue<'a> {
+    F64(f64),
+    I64(i64),
+    U64(u64),
+    I128(i128),
+    U128(u128),
+    Bool(bool),
+    Str(&'a str),
+    Error(&'a (dyn Error + 'static)),
+    Debug(&'a dyn Debug),
+}
+
+impl Serialize for FieldValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            FieldValue::F64(value) => serializer.serialize_f64(value),
+            FieldValue::I64(value) => serializer.serialize_i64(value),
+            FieldValue::U64(value) => serializer.serialize_u64(value),
+            FieldValue::I128(value) => serializer.serialize_i128(value),
+            FieldValue::U128(value) => serializer.serialize_u128(value),
+            FieldValue::Bool(value) => serializer.serialize_bool(value),
+            FieldValue::Str(value) => serializer.serialize_str(value),
+            FieldValue::Error(value) => serializer.collect_str(value),
+            FieldValue::Debug(value) => serializer.collect_str(&format_args!("{value:?}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JsonWriter, Timezone};
+    use crate::{tests::Capture, WriteLayer};
+    use serde_json::{json, Value};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn capture<F: crate::WriteFn<tracing_subscriber::Registry> + Sync + 'static>(writer: F) -> String {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), writer)),
+            || {
+                tracing::info_span!("request", method = "GET").in_scope(|| {
+                    tracing::info!(user = "noel", "hello");
+                });
+            },
+        );
+
+        capture.contents()
+    }
+
+    #[test]
+    fn default_shape() {
+        let output = capture(super::json);
+        let object: Value = serde_json::from_str(output.trim_end()).unwrap();
+
+        // check the order that the keys were streamed in from the output itself, as the
+        // order of `serde_json::Map` depends on its `preserve_order` feature
+        let keys = [
+            "@timestamp",
+            "message",
+            "metadata.module",
+            "metadata.file",
+            "metadata.line",
+            "thread.name",
+            "process.id",
+            "spans",
+            "fields",
+        ];
+
+        let positions = keys
+            .iter()
+            .map(|key| {
+                output
+                    .find(&format!("\"{key}\":"))
+                    .unwrap_or_else(|| panic!("missing key {key}"))
+            })
+            .collect::<Vec<_>>();
+
+        assert!(positions.is_sorted(), "keys were not written in order: {output}");
+        assert_eq!(object.as_object().unwrap().len(), keys.len());
+
+        assert_eq!(object["message"], "hello");
+        assert_eq!(object["fields"], json!({ "user": "noel" }));
+        assert_eq!(object["spans"][0]["name"], "request");
+        assert_eq!(object["spans"][0]["fields"], json!({ "method": "GET" }));
+    }
+
+    #[test]
+    fn streaming_matches_adapter() {
+        let strip = |output: String| {
+            let mut object: Value = serde_json::from_str(output.trim_end()).unwrap();
+            object.as_object_mut().unwrap().remove("@timestamp");
+            object
+        };
+
+        assert_eq!(strip(capture(super::json)), strip(capture(JsonWriter::default())));
+    }
+
+    #[test]
+    fn configured() {
+        let writer = JsonWriter::default()
+            .with_timezone(Timezone::Utc)
+            .with_timestamp_fmt("%Y")
+            .flatten_fields(true)
+            .emit_spans(false)
+            .rename_key("@timestamp", "time")
+            .rename_key("message", "msg")
+            .with_field("service", "azalia")
+            .with_field("msg", "is replaced")
+            .pretty(true);
+
+        let output = capture(writer);
+        assert!(output.contains("\n  \""), "expected pretty output: {output}");
+
+        let object: Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(object["time"].as_str().unwrap().len(), 4);
+        assert_eq!(object["msg"], "hello");
+        assert_eq!(object["user"], "noel");
+        assert_eq!(object["service"], "azalia");
+        assert!(object.get("@timestamp").is_none());
+        assert!(object.get("spans").is_none());
+        assert!(object.get("fields").is_none());
+    }
+
+    #[test]
+    fn invalid_timestamp_fmt() {
+        let output = capture(JsonWriter::default().with_timestamp_fmt("%Y-%Q"));
+        let object: Value = serde_json::from_str(output.trim_end()).unwrap();
+        let timestamp = object["@timestamp"].as_str().unwrap();
+
+        assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok(), "{timestamp}");
+        assert_eq!(object["message"], "hello");
+    }
 }
@@ -0,0 +1,224 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::JsonExtension;
+use chrono::Local;
+use serde_json::Value;
+use std::fmt::{self, Write};
+use tracing::{
+    field::{Field, Visit},
+    Event, Metadata,
+};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
+
+/// Provides a [logfmt](https://brandur.org/logfmt) [`WriteFn`](crate::WriteFn) implementation, which emits
+/// lines like:
+///
+/// ```text
+/// ts=2025-01-01T00:00:00+00:00 level=info target=my_crate msg="hello world" user=noel http.method=GET
+/// ```
+///
+/// Values are only quoted when they need to be (`msg` is always quoted), and the fields of each span
+/// in the event's scope are appended with the span's name as the prefix of the key.
+pub fn logfmt<S: for<'l> LookupSpan<'l>>(event: &Event, metadata: &Metadata, spans: Vec<SpanRef<'_, S>>) -> String {
+    let mut buf = String::new();
+    let _ = write!(
+        buf,
+        "ts={} level={} target=",
+        Local::now().to_rfc3339(),
+        metadata.level().as_str().to_lowercase()
+    );
+
+    write_value(&mut buf, metadata.target());
+
+    let mut visitor = Visitor {
+        message: None,
+        fields: String::new(),
+    };
+
+    event.record(&mut visitor);
+
+    buf.push_str(" msg=");
+    write_quoted(&mut buf, visitor.message.as_deref().unwrap_or_default());
+    buf.push_str(&visitor.fields);
+
+    for span in spans.iter() {
+        let ext = span.extensions();
+        let storage = ext.get::<JsonExtension>().unwrap();
+
+        for (key, value) in &storage.0 {
+            buf.push(' ');
+            write_key(&mut buf, span.name());
+            buf.push('.');
+            write_key(&mut buf, key);
+            buf.push('=');
+
+            match value {
+                Value::String(s) => write_value(&mut buf, s),
+                value => write_value(&mut buf, &value.to_string()),
+            }
+        }
+    }
+
+    buf
+}
+
+struct Visitor {
+    message: Option<String>,
+    fields: String,
+}
+
+impl Visitor {
+    fn field(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = Some(value.to_owned()),
+
+            // don't write the fields that `tracing-log` uses to normalize metadata
+            name if name.starts_with("log.") => {}
+            name => {
+                self.fields.push(' ');
+                write_key(&mut self.fields, name);
+                self.fields.push('=');
+                write_value(&mut self.fields, value);
+            }
+        }
+    }
+}
+
+impl Visit for Visitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.field(field, value);
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.field(field, &value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.field(field, &format!("{value:?}"));
+    }
+}
+
+/// Writes `key`, replacing characters that can't be used in a logfmt key with `_`.
+fn write_key(buf: &mut String, key: &str) {
+    if key.is_empty() {
+        buf.push('_');
+        return;
+    }
+
+    buf.extend(key.chars().map(|c| match c {
+        '=' | '"' => '_',
+        c if c.is_whitespace() || c.is_control() => '_',
+        c => c,
+    }));
+}
+
+/// Writes `value`, quoting it only if it is empty or contains characters that
+/// would be ambiguous in a logfmt line.
+fn write_value(buf: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| matches!(c, '=' | '"' | '\\') || c.is_whitespace() || c.is_control());
+
+    if needs_quotes {
+        write_quoted(buf, value);
+    } else {
+        buf.push_str(value);
+    }
+}
+
+fn write_quoted(buf: &mut String, value: &str) {
+    buf.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(buf, "\\u{:04x}", c as u32);
+            }
+
+            c => buf.push(c),
+        }
+    }
+
+    buf.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_key, write_value};
+    use crate::{tests::Capture, WriteLayer};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn quoting() {
+        let cases = [
+            ("plain", "plain"),
+            ("", r#""""#),
+            ("hello world", r#""hello world""#),
+            ("a=b", r#""a=b""#),
+            (r#"say "hi""#, r#""say \"hi\"""#),
+            ("C:\\path", r#""C:\\path""#),
+            ("line\nbreak", r#""line\nbreak""#),
+            ("\u{1b}[0m", r#""\u001b[0m""#),
+        ];
+
+        for (input, expected) in cases {
+            let mut buf = String::new();
+            write_value(&mut buf, input);
+
+            assert_eq!(buf, expected, "input: {input:?}");
+        }
+
+        let mut buf = String::new();
+        write_key(&mut buf, "a key=\"x\"");
+        assert_eq!(buf, "a_key__x_");
+    }
+
+    #[test]
+    fn lines() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), super::logfmt));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", method = "GET", path = "/hello world");
+            span.in_scope(|| {
+                let inner = tracing::debug_span!("db", rows = 2);
+                inner.in_scope(|| {
+                    tracing::info!(target: "app", user = "noel", ok = true, "said \"hi\"");
+                });
+            });
+        });
+
+        let output = capture.contents();
+        let (ts, line) = output.split_once(' ').unwrap();
+
+        assert!(ts.starts_with("ts="));
+        assert_eq!(
+            line,
+            "level=info target=app msg=\"said \\\"hi\\\"\" user=noel ok=true request.method=GET request.path=\"/hello world\" db.rows=2\n"
+        );
+    }
+}
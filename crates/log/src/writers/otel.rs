@@ -0,0 +1,200 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! [`WriteFn`] that emits JSON records following the [OpenTelemetry log data model].
+//!
+//! Every record contains `Timestamp` (nanoseconds since the Unix epoch), `SeverityText`,
+//! `SeverityNumber`, `Body`, `Attributes`, `Resource` and `InstrumentationScope`, which is named
+//! after the event's target. The fields of the event and its spans are written as `Attributes`,
+//! along with the `code.*` and `thread.name` semantic conventions; the first error that was
+//! recorded on the event is written as `exception.message` and `exception.type`, where the type
+//! is left out if it can't be told from the error.
+//!
+//! [OpenTelemetry log data model]: https://opentelemetry.io/docs/specs/otel/logs/data-model/
+
+use crate::{
+    writers::{Fields, FmtWriter},
+    Spans, WriteFn,
+};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, fmt, process};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
+
+/// `service.name` that is used if no service name was configured, as the OpenTelemetry
+/// specification requires.
+pub const UNKNOWN_SERVICE: &str = "unknown_service";
+
+/// Writer that emits OpenTelemetry log records, see the [module documentation](self).
+#[derive(Debug, Clone, Default)]
+pub struct Writer {
+    /// Attributes of the [`Resource`](https://opentelemetry.io/docs/specs/otel/resource/sdk/) that
+    /// is emitting the events, like `service.name`.
+    pub resource: BTreeMap<String, Value>,
+}
+
+impl Writer {
+    /// Sets the `service.name` resource attribute.
+    pub fn with_service_name<S: Into<String>>(self, name: S) -> Self {
+        self.with_resource("service.name", name.into())
+    }
+
+    /// Sets a resource attribute, like `service.version` or `deployment.environment`.
+    pub fn with_resource<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.resource.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl<L: Subscriber + for<'a> LookupSpan<'a>> WriteFn<L> for Writer {
+    fn write_event(
+        &self,
+        event: &Event,
+        metadata: &Metadata,
+        spans: Spans<'_, L>,
+        buf: &mut dyn fmt::Write,
+    ) -> fmt::Result {
+        serde_json::to_writer(FmtWriter(buf), &self.record(event, metadata, &spans)).map_err(|_| fmt::Error)
+    }
+}
+
+impl Writer {
+    /// Builds the record as a [`Value`] rather than streaming it like
+    /// [`JsonWriter`](super::JsonWriter), since `Attributes` and `Resource` are merged from
+    /// several sources where a later key replaces an earlier one (`code.*` and `exception.*`
+    /// over event fields, configured resources over the defaults), so their keys are
+    /// deduplicated in a [`Map`] first.
+    fn record<L: Subscriber + for<'a> LookupSpan<'a>>(
+        &self,
+        event: &Event<'_>,
+        metadata: &Metadata<'_>,
+        spans: &Spans<'_, L>,
+    ) -> Value {
+        let thread = std::thread::current();
+        let Fields {
+            message,
+            error,
+            error_type,
+            fields,
+        } = Fields::collect(event, spans);
+
+        let mut attributes = Map::from_iter(fields);
+        attributes.insert("thread.name".into(), thread.name().unwrap_or("main").into());
+        if let Some(module) = metadata.module_path() {
+            attributes.insert("code.namespace".into(), module.into());
+        }
+
+        if let Some(file) = metadata.file() {
+            attributes.insert("code.filepath".into(), file.into());
+        }
+
+        if let Some(line) = metadata.line() {
+            attributes.insert("code.lineno".into(), line.into());
+        }
+
+        if let Some(error) = error {
+            attributes.insert("exception.message".into(), error.into());
+        }
+
+        if let Some(error_type) = error_type {
+            attributes.insert("exception.type".into(), error_type.into());
+        }
+
+        let mut resource = Map::new();
+        resource.insert("service.name".into(), UNKNOWN_SERVICE.into());
+        resource.insert("process.pid".into(), process::id().into());
+        resource.extend(self.resource.iter().map(|(key, value)| (key.clone(), value.clone())));
+
+        json!({
+            // u64 nanoseconds don't fit in a JSON number without losing precision
+            "Timestamp": Utc::now().timestamp_nanos_opt().unwrap_or_default().to_string(),
+            "SeverityText": metadata.level().as_str(),
+            "SeverityNumber": severity_number(metadata.level()),
+            "Body": message,
+            "Attributes": attributes,
+            "Resource": resource,
+            "InstrumentationScope": {
+                "Name": metadata.target(),
+            },
+        })
+    }
+}
+
+/// Maps a [`Level`] to the first `SeverityNumber` of its range.
+fn severity_number(level: &Level) -> u8 {
+    match *level {
+        Level::TRACE => 1,
+        Level::DEBUG => 5,
+        Level::INFO => 9,
+        Level::WARN => 13,
+        Level::ERROR => 17,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Writer, UNKNOWN_SERVICE};
+    use crate::{tests::Capture, WriteLayer};
+    use serde_json::{json, Value};
+    use std::io;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn capture(writer: Writer, f: impl FnOnce()) -> Value {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), writer)),
+            f,
+        );
+
+        serde_json::from_str(capture.contents().trim_end()).unwrap()
+    }
+
+    #[test]
+    fn record() {
+        let record = capture(Writer::default().with_service_name("azalia"), || {
+            let err = io::Error::other("disk is on fire");
+            tracing::info_span!("request", method = "GET").in_scope(|| {
+                tracing::warn!(target: "app", error = &err as &dyn std::error::Error, rows = 2, "oh no");
+            });
+        });
+
+        assert!(record["Timestamp"].as_str().unwrap().parse::<i64>().is_ok());
+        assert_eq!(record["SeverityText"], "WARN");
+        assert_eq!(record["SeverityNumber"], 13);
+        assert_eq!(record["Body"], "oh no");
+        assert_eq!(record["InstrumentationScope"], json!({ "Name": "app" }));
+        assert_eq!(record["Attributes"]["method"], "GET");
+        assert_eq!(record["Attributes"]["rows"], 2);
+        assert_eq!(record["Attributes"]["exception.message"], "disk is on fire");
+        assert_eq!(record["Attributes"]["exception.type"], "std::io::Error");
+        assert_eq!(record["Resource"]["service.name"], "azalia");
+        assert_eq!(record["Resource"]["process.pid"], json!(std::process::id()));
+    }
+
+    #[test]
+    fn unknown_service() {
+        let record = capture(Writer::default(), || tracing::trace!("hello"));
+
+        assert_eq!(record["SeverityNumber"], 1);
+        assert_eq!(record["Resource"]["service.name"], UNKNOWN_SERVICE);
+    }
+}
@@ -0,0 +1,768 @@
+// 🐻‍❄️🪚 azalia: Noelware's Rust commons library.
+// Copyright (c) 2024-2025 Noelware, LLC. <team@noelware.org>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! [`WriteFn`] that formats events as syslog messages, and a [`Socket`] to send them to a
+//! syslog daemon with.
+//!
+//! Messages follow [RFC 5424] by default. The fields of the event and of each span that it is
+//! in are written as structured data, with the span's name (or `fields` for the event itself)
+//! as the SD-ID:
+//!
+//! ```text
+//! <14>1 2025-01-01T00:00:00.000000+00:00 - my-app 1234 - [request@32473 method="GET"][fields@32473 user="noel"] hello world
+//! ```
+//!
+//! The [RFC 3164] (BSD syslog) format is also supported for older daemons, which writes fields
+//! as `key=value` pairs after the message as it has no structured data.
+//!
+//! ## Example
+//! ```rust,no_run
+//! use azalia_log::{writers::syslog::{Facility, Socket, Writer}, WriteLayer};
+//! use tracing_subscriber::prelude::*;
+//!
+//! let writer = Writer::default().with_facility(Facility::Local0).with_app_name("my-app");
+//! tracing_subscriber::registry()
+//!     .with(WriteLayer::new_with(Socket::unix().unwrap(), writer))
+//!     .init();
+//! ```
+//!
+//! [RFC 5424]: https://datatracker.ietf.org/doc/html/rfc5424
+//! [RFC 3164]: https://datatracker.ietf.org/doc/html/rfc3164
+
+use crate::{JsonExtension, Spans, WriteFn};
+use chrono::Local;
+use serde_json::Value;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Write},
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    process,
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Metadata, Subscriber,
+};
+use tracing_subscriber::registry::LookupSpan;
+
+#[cfg(unix)]
+use std::{os::unix::net::UnixDatagram, path::Path};
+
+/// Private enterprise number that is used in SD-IDs by default, which is reserved for
+/// documentation by [RFC 5612](https://datatracker.ietf.org/doc/html/rfc5612).
+pub const DEFAULT_ENTERPRISE_ID: u32 = 32473;
+
+/// Path of the socket that local syslog daemons listen on.
+#[cfg(unix)]
+pub const DEV_LOG: &str = "/dev/log";
+
+/// Facility of the program that is logging, from RFC 5424's section 6.2.1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Facility {
+    /// kernel messages
+    Kern = 0,
+    /// user-level messages
+    #[default]
+    User = 1,
+    /// mail system
+    Mail = 2,
+    /// system daemons
+    Daemon = 3,
+    /// security/authorization messages
+    Auth = 4,
+    /// messages generated internally by syslogd
+    Syslog = 5,
+    /// line printer subsystem
+    Lpr = 6,
+    /// network news subsystem
+    News = 7,
+    /// UUCP subsystem
+    Uucp = 8,
+    /// clock daemon
+    Cron = 9,
+    /// security/authorization messages (private)
+    AuthPriv = 10,
+    /// FTP daemon
+    Ftp = 11,
+    /// local use 0
+    Local0 = 16,
+    /// local use 1
+    Local1 = 17,
+    /// local use 2
+    Local2 = 18,
+    /// local use 3
+    Local3 = 19,
+    /// local use 4
+    Local4 = 20,
+    /// local use 5
+    Local5 = 21,
+    /// local use 6
+    Local6 = 22,
+    /// local use 7
+    Local7 = 23,
+}
+
+/// Format of the messages that the [`Writer`] emits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// [RFC 5424](https://datatracker.ietf.org/doc/html/rfc5424) messages.
+    #[default]
+    Rfc5424,
+
+    /// [RFC 3164](https://datatracker.ietf.org/doc/html/rfc3164) (BSD syslog) messages.
+    Rfc3164,
+}
+
+/// Returns the syslog severity of a [`Level`]. `TRACE` and `DEBUG` are both
+/// mapped to the debug severity.
+pub fn severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        Level::DEBUG | Level::TRACE => 7,
+    }
+}
+
+/// Writer that formats events as syslog messages, see the [module documentation](self).
+#[derive(Debug, Clone)]
+pub struct Writer {
+    /// [`Format`] of the messages.
+    pub format: Format,
+
+    /// [`Facility`] that is used to compute the priority of every message.
+    pub facility: Facility,
+
+    /// name of the program, which is the file name of the current executable by default.
+    pub app_name: Cow<'static, str>,
+
+    /// hostname of the machine. Syslog daemons will fill this in if it is `None` and the
+    /// messages are sent to a local socket.
+    pub hostname: Option<Cow<'static, str>>,
+
+    /// private enterprise number that is used in the SD-IDs of structured data.
+    pub enterprise_id: u32,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|path| path.file_stem().map(|name| name.to_string_lossy().into_owned()));
+
+        Self {
+            format: Format::default(),
+            facility: Facility::default(),
+            app_name: app_name.map(Cow::Owned).unwrap_or(Cow::Borrowed("-")),
+            hostname: None,
+            enterprise_id: DEFAULT_ENTERPRISE_ID,
+        }
+    }
+}
+
+impl Writer {
+    /// Sets the [`Format`] of the messages.
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the [`Facility`] of the messages.
+    pub fn with_facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Sets the name of the program.
+    pub fn with_app_name<S: Into<Cow<'static, str>>>(mut self, name: S) -> Self {
+        self.app_name = name.into();
+        self
+    }
+
+    /// Sets the hostname of the machine.
+    pub fn with_hostname<S: Into<Cow<'static, str>>>(mut self, hostname: S) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    /// Sets the private enterprise number that is used in the SD-IDs of structured data.
+    pub fn with_enterprise_id(mut self, id: u32) -> Self {
+        self.enterprise_id = id;
+        self
+    }
+}
+
+impl<L: Subscriber + for<'a> LookupSpan<'a>> WriteFn<L> for Writer {
+    fn write_event(&self, event: &Event, metadata: &Metadata, spans: Spans<'_, L>, buf: &mut dyn Write) -> fmt::Result {
+        let priority = (self.facility as u8) * 8 + severity(metadata.level());
+        match self.format {
+            Format::Rfc5424 => self.rfc5424(priority, event, &spans, buf),
+            Format::Rfc3164 => self.rfc3164(priority, event, &spans, buf),
+        }
+    }
+}
+
+impl Writer {
+    fn rfc5424<L: Subscriber + for<'a> LookupSpan<'a>>(
+        &self,
+        priority: u8,
+        event: &Event<'_>,
+        spans: &Spans<'_, L>,
+        buf: &mut dyn Write,
+    ) -> fmt::Result {
+        write!(
+            buf,
+            "<{priority}>1 {} ",
+            Local::now().format("%Y-%m-%dT%H:%M:%S%.6f%:z")
+        )?;
+
+        write_header_field(buf, self.hostname.as_deref(), 255)?;
+        buf.write_char(' ')?;
+        write_header_field(buf, Some(&self.app_name), 48)?;
+        write!(buf, " {} - ", process::id())?;
+
+        let mut count = Count::default();
+        event.record(&mut count);
+
+        // SD-IDs must be unique within a message, so the event's own is reserved before the
+        // spans, which might be named `fields` or share their name with another span
+        let mut ids = Vec::new();
+        let fields_id = (count.fields > 0).then(|| self.sd_id("fields", &mut ids));
+
+        let mut has_data = false;
+        for span in spans.from_root() {
+            let ext = span.extensions();
+            let data = &ext.get::<JsonExtension>().unwrap().0;
+            if data.is_empty() {
+                continue;
+            }
+
+            has_data = true;
+            write!(buf, "[{}", self.sd_id(span.name(), &mut ids))?;
+            for (key, value) in data {
+                write_param(buf, key, |buf| match value {
+                    Value::String(s) => buf.write_str(s),
+                    value => write!(buf, "{value}"),
+                })?;
+            }
+
+            buf.write_char(']')?;
+        }
+
+        if let Some(id) = fields_id {
+            has_data = true;
+            write!(buf, "[{id}")?;
+
+            let mut params = Params { buf, result: Ok(()) };
+            event.record(&mut params);
+            params.result?;
+
+            buf.write_char(']')?;
+        }
+
+        if !has_data {
+            buf.write_char('-')?;
+        }
+
+        if count.message {
+            buf.write_char(' ')?;
+            write_message(buf, event)?;
+        }
+
+        Ok(())
+    }
+
+    fn rfc3164<L: Subscriber + for<'a> LookupSpan<'a>>(
+        &self,
+        priority: u8,
+        event: &Event<'_>,
+        spans: &Spans<'_, L>,
+        buf: &mut dyn Write,
+    ) -> fmt::Result {
+        write!(buf, "<{priority}>{} ", Local::now().format("%b %e %H:%M:%S"))?;
+        if let Some(hostname) = &self.hostname {
+            write_header_field(buf, Some(hostname), 255)?;
+            buf.write_char(' ')?;
+        }
+
+        // the TAG is at most 32 alphanumeric characters, but daemons accept any printable ones
+        write_header_field(buf, Some(&self.app_name), 32)?;
+        write!(buf, "[{}]: ", process::id())?;
+        write_message(buf, event)?;
+
+        let mut pairs = Pairs { buf, result: Ok(()) };
+        event.record(&mut pairs);
+        pairs.result?;
+
+        for span in spans.from_root() {
+            let ext = span.extensions();
+            for (key, value) in &ext.get::<JsonExtension>().unwrap().0 {
+                match value {
+                    Value::String(s) => write!(buf, " {}.{key}={s}", span.name())?,
+                    value => write!(buf, " {}.{key}={value}", span.name())?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the SD-ID for `name` that isn't in `ids` yet (by appending `-2`, `-3`, ... to
+    /// the name), with the name truncated so that the whole SD-ID fits in an SD-NAME.
+    fn sd_id(&self, name: &str, ids: &mut Vec<String>) -> String {
+        let enterprise_id = format!("@{}", self.enterprise_id);
+        let mut index = 1;
+        loop {
+            let counter = match index {
+                1 => String::new(),
+                index => format!("-{index}"),
+            };
+
+            let mut id = String::new();
+            let _ = write_sd_name(
+                &mut id,
+                name,
+                32usize.saturating_sub(enterprise_id.len() + counter.len()),
+            );
+            id.push_str(&counter);
+            id.push_str(&enterprise_id);
+
+            if !ids.contains(&id) {
+                ids.push(id.clone());
+                return id;
+            }
+
+            index += 1;
+        }
+    }
+}
+
+/// Writes a header field, which is limited to `max` printable US-ASCII characters, or
+/// the NILVALUE if there is no value.
+fn write_header_field(buf: &mut dyn Write, value: Option<&str>, max: usize) -> fmt::Result {
+    match value.filter(|value| !value.is_empty()) {
+        Some(value) => {
+            for c in value.chars().take(max) {
+                buf.write_char(if c.is_ascii_graphic() { c } else { '_' })?;
+            }
+
+            Ok(())
+        }
+
+        None => buf.write_char('-'),
+    }
+}
+
+/// Writes an SD-NAME, which is at most 32 (or `max`, if it is part of an SD-ID) printable
+/// US-ASCII characters other than `=`, ` `, `]`, `"` and `@`.
+fn write_sd_name(buf: &mut dyn Write, name: &str, max: usize) -> fmt::Result {
+    for c in name.chars().take(max) {
+        match c {
+            '=' | ']' | '"' | '@' => buf.write_char('_')?,
+            c if c.is_ascii_graphic() => buf.write_char(c)?,
+            _ => buf.write_char('_')?,
+        }
+    }
+
+    Ok(())
+}
+
+fn write_param(buf: &mut dyn Write, name: &str, value: impl FnOnce(&mut dyn Write) -> fmt::Result) -> fmt::Result {
+    buf.write_char(' ')?;
+    write_sd_name(buf, name, 32)?;
+    buf.write_str("=\"")?;
+    value(&mut Escape(buf))?;
+    buf.write_char('"')
+}
+
+fn write_message(buf: &mut dyn Write, event: &Event<'_>) -> fmt::Result {
+    let mut message = Message { buf, result: Ok(()) };
+    event.record(&mut message);
+    message.result
+}
+
+/// Escapes `"`, `\` and `]` in PARAM-VALUEs.
+struct Escape<'a>(&'a mut dyn Write);
+impl Write for Escape<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if matches!(c, '"' | '\\' | ']') {
+                self.0.write_char('\\')?;
+            }
+
+            self.0.write_char(c)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the event has a message and how many other fields it has.
+#[derive(Default)]
+struct Count {
+    message: bool,
+    fields: usize,
+}
+
+impl Visit for Count {
+    fn record_debug(&mut self, field: &Field, _: &dyn Debug) {
+        match field.name() {
+            "message" => self.message = true,
+            name if name.starts_with("log.") => {}
+            _ => self.fields += 1,
+        }
+    }
+}
+
+struct Message<'a> {
+    buf: &'a mut dyn Write,
+    result: fmt::Result,
+}
+
+impl Visit for Message<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.result = write!(self.buf, "{value:?}");
+        }
+    }
+}
+
+/// Writes the event's fields as SD-PARAMs.
+struct Params<'a> {
+    buf: &'a mut dyn Write,
+    result: fmt::Result,
+}
+
+impl Visit for Params<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.result.is_ok() && field.name() != "message" && !field.name().starts_with("log.") {
+            self.result = write_param(self.buf, field.name(), |buf| buf.write_str(value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if self.result.is_ok() && field.name() != "message" && !field.name().starts_with("log.") {
+            self.result = write_param(self.buf, field.name(), |buf| write!(buf, "{value:?}"));
+        }
+    }
+}
+
+/// Writes the event's fields as `key=value` pairs.
+struct Pairs<'a> {
+    buf: &'a mut dyn Write,
+    result: fmt::Result,
+}
+
+impl Visit for Pairs<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if self.result.is_ok() && field.name() != "message" && !field.name().starts_with("log.") {
+            self.result = write!(self.buf, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if self.result.is_ok() && field.name() != "message" && !field.name().starts_with("log.") {
+            self.result = write!(self.buf, " {}={value:?}", field.name());
+        }
+    }
+}
+
+/// [`Write`](io::Write) implementation that sends messages to a syslog daemon.
+///
+/// Every write is sent as its own message, which is the case for every event that a
+/// [`WriteLayer`](crate::WriteLayer) writes. Messages over TCP are prefixed with their length
+/// (the octet-counting framing from [RFC 6587]) so that multi-line messages stay intact.
+///
+/// [RFC 6587]: https://datatracker.ietf.org/doc/html/rfc6587#section-3.4.1
+#[derive(Debug)]
+pub struct Socket(Transport);
+
+#[derive(Debug)]
+enum Transport {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+}
+
+impl Socket {
+    /// Connects to the local syslog daemon at [`/dev/log`](DEV_LOG).
+    #[cfg(unix)]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(unix)))]
+    pub fn unix() -> io::Result<Socket> {
+        Socket::unix_at(DEV_LOG)
+    }
+
+    /// Connects to a syslog daemon that listens on the Unix datagram socket at `path`.
+    #[cfg(unix)]
+    #[cfg_attr(any(docsrs, noeldoc), doc(cfg(unix)))]
+    pub fn unix_at<P: AsRef<Path>>(path: P) -> io::Result<Socket> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+
+        Ok(Socket(Transport::Unix(socket)))
+    }
+
+    /// Sends messages as UDP datagrams to the syslog daemon at `addr`. Each address that `addr`
+    /// resolves to is tried in order, with a local socket of the same address family.
+    pub fn udp<A: ToSocketAddrs>(addr: A) -> io::Result<Socket> {
+        let mut last_error = None;
+        for addr in addr.to_socket_addrs()? {
+            let local = match addr {
+                SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+                SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+            };
+
+            match UdpSocket::bind(local).and_then(|socket| socket.connect(addr).map(|()| socket)) {
+                Ok(socket) => return Ok(Socket(Transport::Udp(socket))),
+                Err(error) => last_error = Some(error),
+            }
+        }
+
+        Err(last_error
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "could not resolve to any addresses")))
+    }
+
+    /// Connects to the syslog daemon at `addr` over TCP.
+    pub fn tcp<A: ToSocketAddrs>(addr: A) -> io::Result<Socket> {
+        Ok(Socket(Transport::Tcp(TcpStream::connect(addr)?)))
+    }
+}
+
+impl io::Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // messages are delimited by the transport or their length, so they don't need the trailing newline
+        let message = buf.strip_suffix(b"\n").unwrap_or(buf);
+        match &mut self.0 {
+            #[cfg(unix)]
+            Transport::Unix(socket) => socket.send(message).map(|_| buf.len()),
+            Transport::Udp(socket) => socket.send(message).map(|_| buf.len()),
+            Transport::Tcp(stream) => {
+                let mut frame = format!("{} ", message.len()).into_bytes();
+                frame.extend_from_slice(message);
+
+                stream.write_all(&frame).map(|()| buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.0 {
+            Transport::Tcp(stream) => stream.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Facility, Format, Socket, Writer};
+    use crate::{tests::Capture, WriteLayer};
+    use std::{
+        io::Read,
+        net::{TcpListener, UdpSocket},
+        process,
+    };
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn capture(writer: Writer) -> String {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(capture.clone(), writer)),
+            || {
+                tracing::info_span!("request", method = "GET", path = "/a]b").in_scope(|| {
+                    tracing::warn!(user = "say \"hi\"", retries = 2, "hello world");
+                });
+            },
+        );
+
+        capture.contents()
+    }
+
+    #[test]
+    fn rfc5424() {
+        let writer = Writer::default()
+            .with_facility(Facility::Local0)
+            .with_app_name("my app")
+            .with_hostname("example.com");
+
+        let output = capture(writer);
+        let (header, rest) = output.split_once(' ').unwrap();
+        assert_eq!(header, "<132>1");
+
+        // skip the timestamp
+        let (_, rest) = rest.split_once(' ').unwrap();
+        assert_eq!(
+            rest,
+            format!(
+                "example.com my_app {} - [request@32473 method=\"GET\" path=\"/a\\]b\"][fields@32473 user=\"say \\\"hi\\\"\" retries=\"2\"] hello world\n",
+                process::id()
+            )
+        );
+    }
+
+    #[test]
+    fn rfc3164() {
+        let output = capture(Writer::default().with_format(Format::Rfc3164).with_app_name("app"));
+
+        assert!(output.starts_with("<12>"));
+        assert!(output.ends_with(&format!(
+            " app[{}]: hello world user=say \"hi\" retries=2 request.method=GET request.path=/a]b\n",
+            process::id()
+        )));
+    }
+
+    #[test]
+    fn without_structured_data() {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(
+                capture.clone(),
+                Writer::default().with_app_name("app"),
+            )),
+            || tracing::error!("oh no"),
+        );
+
+        assert!(capture
+            .contents()
+            .ends_with(&format!(" - app {} - - oh no\n", process::id())));
+    }
+
+    #[test]
+    fn unique_sd_ids() {
+        let capture = Capture::default();
+        tracing::subscriber::with_default(
+            tracing_subscriber::registry().with(WriteLayer::new_with(
+                capture.clone(),
+                Writer::default().with_app_name("app"),
+            )),
+            || {
+                tracing::info_span!("fields", a = 1).in_scope(|| {
+                    tracing::info_span!("request", b = 2).in_scope(|| {
+                        tracing::info_span!("request", c = 3).in_scope(|| {
+                            tracing::info_span!("a_span_name_that_is_way_too_long_for_syslog", d = 4)
+                                .in_scope(|| tracing::info!(e = 5, "hello"));
+                        });
+                    });
+                });
+            },
+        );
+
+        assert!(
+            capture.contents().ends_with(&format!(
+                " - app {} - [fields-2@32473 a=\"1\"][request@32473 b=\"2\"][request-2@32473 c=\"3\"]\
+                 [a_span_name_that_is_way_to@32473 d=\"4\"][fields@32473 e=\"5\"] hello\n",
+                process::id()
+            )),
+            "{}",
+            capture.contents()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.sock");
+        let listener = std::os::unix::net::UnixDatagram::bind(&path).unwrap();
+
+        let layer = WriteLayer::new_with(Socket::unix_at(&path).unwrap(), Writer::default().with_app_name("app"));
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::info!("first");
+            tracing::debug!("second");
+        });
+
+        let mut buf = [0; 1024];
+        let len = listener.recv(&mut buf).unwrap();
+        let first = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(first.starts_with("<14>1 "));
+        assert!(first.ends_with(" - - first"), "{first}");
+
+        let len = listener.recv(&mut buf).unwrap();
+        let second = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(second.starts_with("<15>1 "));
+        assert!(second.ends_with(" - - second"), "{second}");
+    }
+
+    #[test]
+    fn udp_socket() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let layer = WriteLayer::new_with(
+            Socket::udp(listener.local_addr().unwrap()).unwrap(),
+            Writer::default().with_format(Format::Rfc3164).with_app_name("app"),
+        );
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::error!("oh no");
+        });
+
+        let mut buf = [0; 1024];
+        let len = listener.recv(&mut buf).unwrap();
+        let message = std::str::from_utf8(&buf[..len]).unwrap();
+
+        assert!(message.starts_with("<11>"));
+        assert!(message.ends_with(&format!(" app[{}]: oh no", process::id())));
+    }
+
+    #[test]
+    fn udp_socket_ipv6() {
+        let Ok(listener) = UdpSocket::bind("[::1]:0") else {
+            // IPv6 isn't available
+            return;
+        };
+
+        let mut socket = Socket::udp(listener.local_addr().unwrap()).unwrap();
+        std::io::Write::write_all(&mut socket, b"<14>hello\n").unwrap();
+
+        let mut buf = [0; 1024];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"<14>hello");
+    }
+
+    #[test]
+    fn tcp_socket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let layer = WriteLayer::new_with(
+            Socket::tcp(listener.local_addr().unwrap()).unwrap(),
+            Writer::default().with_format(Format::Rfc3164).with_app_name("my app"),
+        );
+
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+            tracing::error!("multiple\nlines");
+            tracing::error!("oh no");
+        });
+
+        let mut output = String::new();
+        listener.accept().unwrap().0.read_to_string(&mut output).unwrap();
+
+        let mut frames = Vec::new();
+        let mut rest = output.as_str();
+        while let Some((len, message)) = rest.split_once(' ') {
+            let (message, next) = message.split_at(len.parse().unwrap());
+            frames.push(message);
+            rest = next;
+        }
+
+        assert_eq!(frames.len(), 2, "{output:?}");
+        assert!(frames[0].ends_with(&format!(" my_app[{}]: multiple\nlines", process::id())));
+        assert!(frames[1].ends_with(&format!(" my_app[{}]: oh no", process::id())));
+    }
+}